@@ -0,0 +1,185 @@
+//! Assembles a custom font in memory, e.g. a reduced ASCII-only face to shrink binaries.
+//!
+//! The builder borrows the bitmaps and character lists it is given and keeps them in a
+//! fixed-capacity array, so it works without `alloc`; bytes are only produced by
+//! [`FontBuilder::build`], into a caller-provided buffer.
+use crate::error::PsfError;
+use crate::glyph::Glyph;
+use crate::psf::PSF2Font;
+use crate::writer::{glyph_size, write_header, END_REC, HEADER_SIZE};
+
+/// Number of glyphs a [`FontBuilder`] holds when no capacity is given.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+#[derive(Clone, Copy)]
+struct Entry<'b> {
+    bitmap: &'b [u8],
+    chars: &'b [char],
+    /// Glyph index in the output font; shared by deduplicated entries.
+    index: u32,
+}
+
+/// Accumulates glyphs and their characters, then emits a PSF2 blob.
+///
+/// Glyph indices are assigned in insertion order. Up to `N` glyphs can be added;
+/// use [`FontBuilder::with_capacity`] to pick `N`.
+///
+/// ```rust
+/// use spleen_font::FontBuilder;
+///
+/// let bar = [0xFF; 4];
+/// let dot = [0x00, 0x18, 0x18, 0x00];
+///
+/// let mut buf = [0u8; 64];
+/// let font = FontBuilder::new(8, 4)
+///     .add_glyph(&bar, &['|', '│'])
+///     .add_glyph(&dot, &['.'])
+///     .build_font(&mut buf)
+///     .unwrap();
+///
+/// assert_eq!(font.num_glyphs, 2);
+/// assert_eq!(font.glyph_by_idx(1), Some(&dot[..]));
+/// ```
+#[derive(Clone, Copy)]
+pub struct FontBuilder<'b, const N: usize = DEFAULT_CAPACITY> {
+    width: u32,
+    height: u32,
+    entries: [Entry<'b>; N],
+    len: usize,
+    /// Number of distinct glyph indices handed out so far.
+    num_glyphs: u32,
+    dedup: bool,
+    /// Set when `add_glyph` is called on a full builder; reported by `build`.
+    overflowed: bool,
+}
+
+impl<'b> FontBuilder<'b> {
+    /// Creates a builder for `width × height` glyphs with room for [`DEFAULT_CAPACITY`] glyphs.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::with_capacity(width, height)
+    }
+}
+
+impl<'b, const N: usize> FontBuilder<'b, N> {
+    /// Creates a builder with room for `N` glyphs, e.g. `FontBuilder::<96>::with_capacity(8, 16)`.
+    pub fn with_capacity(width: u32, height: u32) -> Self {
+        FontBuilder {
+            width,
+            height,
+            entries: [Entry {
+                bitmap: &[],
+                chars: &[],
+                index: 0,
+            }; N],
+            len: 0,
+            num_glyphs: 0,
+            dedup: false,
+            overflowed: false,
+        }
+    }
+
+    /// When enabled, a bitmap identical (pixel for pixel) to one already added reuses
+    /// that glyph's index, and its characters are merged into the existing record.
+    /// Only affects glyphs added afterwards.
+    pub fn dedup(self, dedup: bool) -> Self {
+        FontBuilder { dedup, ..self }
+    }
+
+    /// Size of each glyph in bytes; every bitmap passed to `add_glyph` must be this long.
+    /// `u32::MAX` if the dimensions need more than a PSF2 header can hold, which
+    /// [`build`](Self::build) rejects.
+    pub fn bytes_per_glyph(&self) -> u32 {
+        glyph_size(self.width, self.height).unwrap_or(u32::MAX)
+    }
+
+    /// Number of glyphs the built font will contain.
+    pub fn num_glyphs(&self) -> u32 {
+        self.num_glyphs
+    }
+
+    /// Adds a glyph with the characters that should map to it.
+    ///
+    /// Problems (a full builder, a wrongly-sized bitmap) are reported by `build`,
+    /// so calls can be chained.
+    pub fn add_glyph(mut self, bitmap: &'b [u8], chars: &'b [char]) -> Self {
+        if self.len == N {
+            self.overflowed = true;
+            return self;
+        }
+
+        let width = self.width as usize;
+        let existing = self.entries[..self.len]
+            .iter()
+            .find(|e| self.dedup && Glyph::new(e.bitmap, width) == Glyph::new(bitmap, width));
+        let index = match existing {
+            Some(e) => e.index,
+            None => {
+                self.num_glyphs += 1;
+                self.num_glyphs - 1
+            }
+        };
+
+        self.entries[self.len] = Entry {
+            bitmap,
+            chars,
+            index,
+        };
+        self.len += 1;
+        self
+    }
+
+    /// Exact number of bytes [`build`](Self::build) needs.
+    pub fn encoded_len(&self) -> usize {
+        let entries = &self.entries[..self.len];
+        let chars: usize = entries
+            .iter()
+            .flat_map(|e| e.chars)
+            .map(|c| c.len_utf8())
+            .sum();
+        let num_glyphs = self.num_glyphs as usize;
+        let bitmaps = num_glyphs.saturating_mul(self.bytes_per_glyph() as usize);
+        (HEADER_SIZE + chars + num_glyphs).saturating_add(bitmaps)
+    }
+
+    /// Writes the font into `buf` and returns the number of bytes written.
+    pub fn build(&self, buf: &mut [u8]) -> Result<usize, PsfError> {
+        let entries = &self.entries[..self.len];
+        if self.overflowed {
+            return Err(PsfError::CapacityExceeded);
+        }
+        let size = glyph_size(self.width, self.height).ok_or(PsfError::InvalidDimensions)?;
+        let bytes_per_glyph = size as usize;
+        if bytes_per_glyph == 0 || entries.iter().any(|e| e.bitmap.len() != bytes_per_glyph) {
+            return Err(PsfError::GlyphSizeMismatch);
+        }
+
+        let len = self.encoded_len();
+        let out = buf.get_mut(..len).ok_or(PsfError::BufferTooSmall)?;
+        let (header, rest) = out.split_at_mut(HEADER_SIZE);
+        write_header(header, 1, self.num_glyphs, size, self.width, self.height);
+
+        let (bitmaps, table) = rest.split_at_mut(self.num_glyphs as usize * bytes_per_glyph);
+        let mut p = 0;
+        for (idx, slot) in bitmaps.chunks_exact_mut(bytes_per_glyph).enumerate() {
+            let idx = idx as u32;
+            let mut same = entries.iter().filter(|e| e.index == idx).peekable();
+            if let Some(first) = same.peek() {
+                slot.copy_from_slice(first.bitmap);
+            }
+            for c in same.flat_map(|e| e.chars) {
+                p += c.encode_utf8(&mut table[p..]).len();
+            }
+            table[p] = END_REC;
+            p += 1;
+        }
+
+        Ok(len)
+    }
+
+    /// Builds the font into `buf` and parses it back, borrowing from `buf`.
+    pub fn build_font<'o>(&self, buf: &'o mut [u8]) -> Result<PSF2Font<'o>, PsfError> {
+        let len = self.build(buf)?;
+        let buf: &'o [u8] = buf;
+        PSF2Font::new(&buf[..len])
+    }
+}
@@ -1,62 +1,167 @@
-/// In order to have faster lookup times, we cache glyphs in a simple ring buffer.
-/// Note that this cache is the second fallback - we first check if the glyph index falls within ASCII range.
+//! In order to have faster lookup times, we cache glyphs in a simple ring buffer.
+//! Note that this cache is the second fallback - we first check if the glyph index falls within ASCII range.
+//!
+//! In front of the ring sits a tiny direct-mapped index: each key hashes to one of
+//! [`L1_SLOTS`] slots remembering which ring entry last held it, so the hottest few
+//! keys are found with one comparison instead of a scan of the whole ring.
 
-/// Longest possible key we can copy into the cache.
-/// This covers every UTF-8 scalar (spleen doesn't come with emojis).
+/// Longest key stored verbatim; this covers every UTF-8 scalar and short sequences.
+///
+/// Longer keys (multi-codepoint sequences) are stored as their first
+/// `MAX_KEY_SIZE - 8` bytes followed by a 64-bit FNV-1a hash of the whole key, so they
+/// still cache; two such keys of equal length and prefix would only be confused on a
+/// hash collision. Keys over 255 bytes aren't cached at all.
 const MAX_KEY_SIZE: usize = 16;
 
-/// Maximum size of the cache.
-const CACHE_SIZE: usize = 64;
+/// Slots of the direct-mapped index in front of the ring; a power of two.
+const L1_SLOTS: usize = 8;
+
+/// Marks an index slot that doesn't point at a ring entry yet.
+const EMPTY: usize = usize::MAX;
+
+/// Number of entries a font caches unless told otherwise.
+pub const DEFAULT_CACHE_SIZE: usize = 64;
 
 #[derive(Clone, Copy)]
 struct CacheEntry {
     len: u8,
     key: [u8; MAX_KEY_SIZE],
-    glyph: u32,
+    /// Byte offset of the glyph's bitmap in the font's glyph data. Every glyph has the
+    /// same length, so the offset alone is enough to slice it without re-indexing.
+    offset: u32,
+}
+
+/// Hit/miss counters of a font's glyph cache; see [`PSF2Font::cache_stats`](crate::PSF2Font::cache_stats).
+///
+/// Only lookups that reach the cache are counted: ASCII and fonts without a unicode
+/// table resolve directly and never touch it. Counters wrap on overflow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered from the cache.
+    pub hits: u32,
+    /// Lookups that had to scan the unicode table.
+    pub misses: u32,
 }
 
 /// A simple ring buffer cache for glyphs.
-/// Uses a round-robin insertion cursor.
-pub struct Cache {
-    entries: [CacheEntry; CACHE_SIZE],
+/// Uses a round-robin insertion cursor. A capacity of `0` caches nothing.
+pub struct Cache<const N: usize = DEFAULT_CACHE_SIZE> {
+    entries: [CacheEntry; N],
+    /// For each hash slot, the ring entry its key was last found in or inserted at.
+    /// Entries get overwritten behind its back, so a hit is always re-checked.
+    l1: [usize; L1_SLOTS],
     next: usize,
+    stats: CacheStats,
 }
 
-impl Cache {
+impl<const N: usize> Cache<N> {
     /// Creates a new cache.
     pub const fn new() -> Self {
         Cache {
             entries: [CacheEntry {
                 len: 0,
                 key: [0; MAX_KEY_SIZE],
-                glyph: 0,
-            }; CACHE_SIZE],
+                offset: 0,
+            }; N],
+            l1: [EMPTY; L1_SLOTS],
             next: 0,
+            stats: CacheStats { hits: 0, misses: 0 },
         }
     }
 
-    /// Tries to get a glyph from the cache.
-    pub fn get(&self, key: &[u8]) -> Option<u32> {
-        self.entries.iter().find_map(|e| {
-            if e.len as usize == key.len() && &e.key[..key.len()] == key {
-                Some(e.glyph)
-            } else {
-                None
-            }
-        })
+    /// Tries to get a glyph's offset from the cache, counting the hit or miss.
+    /// A key found in the ring is remembered in the index, so repeating it is cheap.
+    pub fn get(&mut self, key: &[u8]) -> Option<u32> {
+        let found = stored_key(key).and_then(|(len, stored)| {
+            let slot = l1_slot(len, &stored);
+            let i = self.find(slot, len, &stored)?;
+            self.l1[slot] = i;
+            Some(self.entries[i].offset)
+        });
+        if found.is_some() {
+            self.stats.hits = self.stats.hits.wrapping_add(1);
+        } else {
+            self.stats.misses = self.stats.misses.wrapping_add(1);
+        }
+        found
     }
 
-    /// Inserts a glyph into the the cache.
+    /// Like [`get`](Self::get), without touching the counters or the index.
+    pub fn peek(&self, key: &[u8]) -> Option<u32> {
+        let (len, stored) = stored_key(key)?;
+        let i = self.find(l1_slot(len, &stored), len, &stored)?;
+        Some(self.entries[i].offset)
+    }
+
+    /// The ring entry holding `(len, stored)`: the one index `slot` points at if it
+    /// still does, else the first match in the ring.
+    fn find(&self, slot: usize, len: u8, stored: &[u8; MAX_KEY_SIZE]) -> Option<usize> {
+        let matches = |e: &CacheEntry| {
+            #[cfg(test)]
+            KEY_COMPARISONS.with(|n| n.set(n.get() + 1));
+            e.len == len && e.key == *stored
+        };
+        match self.entries.get(self.l1[slot]) {
+            Some(e) if matches(e) => Some(self.l1[slot]),
+            _ => self.entries.iter().position(matches),
+        }
+    }
+
+    /// Inserts a glyph's offset into the the cache.
     /// Overwrites the next slot if full.
-    pub fn insert(&mut self, key: &[u8], glyph: u32) {
-        if key.len() > MAX_KEY_SIZE {
+    pub fn insert(&mut self, key: &[u8], offset: u32) {
+        let Some((len, stored)) = stored_key(key) else {
+            return;
+        };
+        if N == 0 {
             return;
         }
 
         let entry = &mut self.entries[self.next];
-        entry.len = key.len() as u8;
-        entry.key[..key.len()].copy_from_slice(key);
-        entry.glyph = glyph;
-        self.next = (self.next + 1) % CACHE_SIZE;
+        entry.len = len;
+        entry.key = stored;
+        entry.offset = offset;
+        self.l1[l1_slot(len, &stored)] = self.next;
+        self.next = (self.next + 1) % N;
+    }
+
+    /// The hit/miss counters so far.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+std::thread_local! {
+    /// Cache entries compared against a key on this thread, to check the index works.
+    pub(crate) static KEY_COMPARISONS: core::cell::Cell<u32> = const { core::cell::Cell::new(0) };
+}
+
+/// The index slot for a stored key.
+fn l1_slot(len: u8, stored: &[u8; MAX_KEY_SIZE]) -> usize {
+    let hash = stored.iter().fold(len as usize, |hash, &byte| {
+        hash.wrapping_mul(31) ^ byte as usize
+    });
+    hash & (L1_SLOTS - 1)
+}
+
+/// The `(len, key)` an entry holds for `key`: the key itself, zero-padded, or for long
+/// keys a prefix and hash (see [`MAX_KEY_SIZE`]). `None` if it is too long to cache.
+fn stored_key(key: &[u8]) -> Option<(u8, [u8; MAX_KEY_SIZE])> {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    const PREFIX: usize = MAX_KEY_SIZE - 8;
+
+    let len = u8::try_from(key.len()).ok()?;
+    let mut stored = [0; MAX_KEY_SIZE];
+    if key.len() <= MAX_KEY_SIZE {
+        stored[..key.len()].copy_from_slice(key);
+    } else {
+        let hash = key.iter().fold(OFFSET_BASIS, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        });
+        stored[..PREFIX].copy_from_slice(&key[..PREFIX]);
+        stored[PREFIX..].copy_from_slice(&hash.to_le_bytes());
     }
+    Some((len, stored))
 }
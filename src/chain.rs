@@ -0,0 +1,40 @@
+//! Falling back to other fonts for characters the first one lacks.
+
+use crate::glyph::Glyph;
+use crate::psf::PSF2Font;
+
+/// An ordered list of `N` fonts; lookups try each in turn and report which one matched.
+///
+/// The fonts don't need to share metrics: a fallback may be wider or taller than the
+/// primary font, so position glyphs using the metrics of the font they came from
+/// (see [`FontChain::font`]), not those of the first font.
+pub struct FontChain<'a, const N: usize> {
+    fonts: [PSF2Font<'a>; N],
+}
+
+impl<'a, const N: usize> FontChain<'a, N> {
+    /// Chains `fonts`, the first being the primary one.
+    pub fn new(fonts: [PSF2Font<'a>; N]) -> Self {
+        FontChain { fonts }
+    }
+
+    /// The font at position `index` in the chain.
+    pub fn font(&self, index: usize) -> Option<&PSF2Font<'a>> {
+        self.fonts.get(index)
+    }
+
+    /// Returns the glyph for a UTF-8 byte slice from the first font that has one,
+    /// together with that font's position in the chain.
+    pub fn glyph_for_utf8(&mut self, text: &[u8]) -> Option<(usize, Glyph<'a>)> {
+        self.fonts
+            .iter_mut()
+            .enumerate()
+            .find_map(|(i, font)| Some((i, font.glyph_for_utf8(text)?)))
+    }
+
+    /// Returns the glyph for a single character; see [`FontChain::glyph_for_utf8`].
+    pub fn glyph_for_char(&mut self, c: char) -> Option<(usize, Glyph<'a>)> {
+        let mut buf = [0u8; 4];
+        self.glyph_for_utf8(c.encode_utf8(&mut buf).as_bytes())
+    }
+}
@@ -0,0 +1,84 @@
+//! Errors returned while parsing, validating or producing fonts.
+use core::fmt;
+
+/// Why a font couldn't be parsed, validated or written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsfError {
+    /// Fewer than 32 bytes were available for the PSF2 header.
+    HeaderTooShort,
+    /// The data starts with neither the PSF2 nor the PSF1 magic number.
+    InvalidMagic,
+    /// The PSF2 version field isn't 0.
+    UnsupportedVersion,
+    /// `header_size` is below 32 bytes or points past the end of the data.
+    InvalidHeaderSize,
+    /// The data ends before the glyph bitmaps do: the header asks for `needed` bytes up to
+    /// the end of the last glyph (`usize::MAX` if that overflows), but there are only `got`.
+    DataTooShort { needed: usize, got: usize },
+    /// Glyph data isn't a whole number of `bytes_per_glyph`-sized glyphs, or
+    /// `bytes_per_glyph` doesn't match `width` × `height`.
+    GlyphSizeMismatch,
+    /// `width`, `height`, `num_glyphs` or `bytes_per_glyph` is zero. Fonts with no
+    /// glyphs still parse; only [`PSF2Header::validate`](crate::PSF2Header::validate)
+    /// rejects them.
+    InvalidDimensions,
+    /// A unicode mapping refers to a glyph index the font doesn't have.
+    GlyphIndexOutOfRange,
+    /// The output buffer can't hold the encoded font.
+    BufferTooSmall,
+    /// More glyphs were added to a [`FontBuilder`](crate::FontBuilder) than it has room for.
+    CapacityExceeded,
+    /// A string or pair of dimensions doesn't name a bundled [`Size`](crate::Size).
+    UnknownSize,
+}
+
+impl PsfError {
+    /// A short, static description of the error; usable in `const` contexts.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            PsfError::HeaderTooShort => "PSF2 header is too short",
+            PsfError::InvalidMagic => "unsupported format: not a PSF2 or PSF1 font",
+            PsfError::UnsupportedVersion => "PSF2 version is not supported",
+            PsfError::InvalidHeaderSize => "PSF2 header size is invalid",
+            PsfError::DataTooShort { .. } => "PSF2 data too short",
+            PsfError::GlyphSizeMismatch => "glyph data is not a whole number of glyphs",
+            PsfError::InvalidDimensions => "font width, height, glyph count or glyph size is zero",
+            PsfError::GlyphIndexOutOfRange => "unicode table maps to a glyph that doesn't exist",
+            PsfError::BufferTooSmall => "output buffer too small",
+            PsfError::CapacityExceeded => "font builder capacity exceeded",
+            PsfError::UnknownSize => "not a bundled Spleen size",
+        }
+    }
+}
+
+impl fmt::Display for PsfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PsfError::DataTooShort { needed, got } => {
+                write!(f, "{}: needed {needed} bytes, got {got}", self.as_str())
+            }
+            _ => f.write_str(self.as_str()),
+        }
+    }
+}
+
+/// Why a lookup found no glyph; see [`PSF2Font::try_glyph`](crate::PSF2Font::try_glyph).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupMiss {
+    /// The input isn't complete, well-formed UTF-8.
+    MalformedUtf8,
+    /// The font has no glyph for the character or sequence.
+    NotMapped,
+    /// The character maps to this glyph index, which the font doesn't have.
+    IndexOutOfRange(u32),
+}
+
+impl fmt::Display for LookupMiss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupMiss::MalformedUtf8 => f.write_str("input is not well-formed UTF-8"),
+            LookupMiss::NotMapped => f.write_str("font has no glyph for the input"),
+            LookupMiss::IndexOutOfRange(idx) => write!(f, "glyph index {idx} is out of range"),
+        }
+    }
+}
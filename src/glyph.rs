@@ -1,3 +1,5 @@
+use core::num::NonZeroUsize;
+
 /// Each glyph is essentially a 2D bitmap.
 ///
 /// Example: for an 8x16 font, each glyph is 16 bytes long;
@@ -13,35 +15,633 @@
 /// │ …
 /// └ row h-1: bytes_per_row bytes
 /// ```
+///
+/// Nothing here assumes a maximum width: rows of any number of bytes are read bit by
+/// bit, MSB-first, and the padding bits after `width` are never yielded.
+///
+/// # Computed rows
+///
+/// Transforms that compute new rows, such as [`scaled_xy`](Self::scaled_xy),
+/// [`fit`](Self::fit) or [`outline`](Self::outline), hold each row inline, so their
+/// width is capped at [`MAX_ROW_BYTES`] × 8 = 128 pixels; columns past the cap are cut
+/// off.
+///
+/// # Pairwise operations
+///
+/// [`overlay`](Self::overlay), [`xor`](Self::xor), [`blend`](Self::blend) and
+/// [`hamming_distance`](Self::hamming_distance) match two glyphs pixel for pixel, so they
+/// return `None` unless both have the same width and the same number of remaining rows.
 #[derive(Clone, Copy)]
 pub struct Glyph<'a> {
     /// Raw bytes for the glyph. Taken from the glyph bitmap data.
     data: &'a [u8],
     /// Number of columns (pixels). Taken from the PSF2 header.
     width: usize,
+    /// Rows not yet iterated; `data` holds exactly this many.
+    height: usize,
+    /// Which end of each byte holds the leftmost of its columns.
+    order: BitOrder,
 }
 
-#[derive(Clone, Copy)]
+/// The order of the 8 columns packed into each byte of a row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The high bit is the leftmost column, as PSF fonts store them.
+    #[default]
+    MsbFirst,
+    /// The low bit is the leftmost column.
+    LsbFirst,
+}
+
+/// Widest row, in bytes, that a computed [`GlyphRow`] can hold inline (128 pixels).
+///
+/// Rows borrowed straight from a font have no such limit; this only caps the
+/// rows produced by transforms such as [`Glyph::overlay`].
+pub const MAX_ROW_BYTES: usize = 16;
+
+#[derive(Clone, Copy, Debug)]
 pub struct GlyphRow<'a> {
-    row: &'a [u8],  // ((width+7)>>3) bytes
-    bit_idx: usize, // current bit
+    row: RowBytes<'a>, // ((width+7)>>3) bytes
+    bit_idx: usize,    // current bit
     width: usize,
+    order: BitOrder,
+}
+
+/// Backing storage for a [`GlyphRow`].
+#[derive(Clone, Copy, Debug)]
+enum RowBytes<'a> {
+    /// Borrowed from the font's glyph bitmap data.
+    Borrowed(&'a [u8]),
+    /// Computed by a transform; packed MSB-first like the font data.
+    Owned([u8; MAX_ROW_BYTES]),
 }
 
 impl<'a> Glyph<'a> {
+    /// A glyph of `width` columns with as many whole rows as `slice` holds.
     pub fn new(slice: &'a [u8], width: usize) -> Self {
-        Glyph { data: slice, width }
+        Self::with_height(slice, width, usize::MAX)
+    }
+
+    /// A glyph of `width × height` pixels, as a font lays them out; bytes past the last
+    /// row, such as padding after the bitmap, are never read. A `slice` too short for
+    /// `height` rows gives just the whole rows it has.
+    pub fn with_height(slice: &'a [u8], width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(8);
+        let height = match stride {
+            0 => 0,
+            stride => height.min(slice.len() / stride),
+        };
+        Glyph {
+            data: &slice[..height * stride],
+            width,
+            height,
+            order: BitOrder::MsbFirst,
+        }
+    }
+
+    /// Reads the glyph's bytes in `order` instead of MSB-first, e.g. for bitmaps
+    /// prepared for a blitter that packs columns LSB-first. Rows computed by transforms
+    /// such as [`overlay`](Self::overlay) always come out MSB-first.
+    pub fn bit_order(self, order: BitOrder) -> Self {
+        Glyph { order, ..self }
+    }
+
+    /// Number of bytes per row: `ceil(width / 8)`.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.width.div_ceil(8)
+    }
+
+    /// Width in pixels, as given by the font header.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in pixels, counting only the rows not yet iterated.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Logical `(width, height)` in pixels, counting only the rows not yet iterated.
+    #[inline]
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    /// Returns row `y` (counted from the first remaining row) without advancing the glyph,
+    /// or `None` if `y >= height`.
+    pub fn row(&self, y: usize) -> Option<GlyphRow<'a>> {
+        let stride = self.stride();
+        if stride == 0 {
+            return None;
+        }
+        let start = y.checked_mul(stride)?;
+        let bytes = self.data.get(start..start.checked_add(stride)?)?;
+        Some(GlyphRow::with_bit_order(bytes, self.width, self.order))
+    }
+
+    /// Yields only rows `start..end` (counted from the first remaining row), clamped to
+    /// `height`, e.g. for a display rendered one horizontal band at a time. The rows
+    /// outside are skipped without being read.
+    pub fn rows_range(&self, start: usize, end: usize) -> impl Iterator<Item = GlyphRow<'a>> {
+        let (stride, height) = (self.stride(), self.height());
+        let end = end.min(height);
+        let start = start.min(end);
+        Glyph {
+            data: &self.data[start * stride..end * stride],
+            height: end - start,
+            ..*self
+        }
+    }
+
+    /// Yields the remaining rows with their y-coordinate on screen, for a glyph drawn
+    /// with its top at `y0`: `(y0 + y, row)`. Rows that would land past `usize::MAX`
+    /// end the iterator.
+    pub fn rows_at(&self, y0: usize) -> impl Iterator<Item = (usize, GlyphRow<'a>)> {
+        self.enumerate()
+            .map_while(move |(y, row)| Some((y0.checked_add(y)?, row)))
+    }
+
+    /// Row `y` (counted from the first remaining row) as its single packed byte, for
+    /// glyphs at most 8 pixels wide; `None` for wider glyphs or if `y >= height`. The
+    /// byte is as stored, so the `8 - width` padding bits are included.
+    #[inline]
+    pub fn fast_row_u8(&self, y: usize) -> Option<u8> {
+        // A zero-width glyph has no rows to return.
+        if !(1..=8).contains(&self.width) {
+            return None;
+        }
+        self.data.get(y).copied()
+    }
+
+    /// Yields each remaining row as its packed `stride()`-byte slice, MSB-first,
+    /// for blitters that consume packed bitmaps directly.
+    pub fn row_bytes(&self) -> impl Iterator<Item = &'a [u8]> {
+        self.data.chunks_exact(self.stride().max(1))
+    }
+
+    /// Counts the set pixels of the remaining rows, e.g. to spot blank glyphs or weigh
+    /// them. Padding bits past `width` are masked off, so the count is exact even when
+    /// the width isn't a multiple of 8.
+    pub fn set_pixel_count(&self) -> usize {
+        let stride = self.stride();
+        if stride == 0 {
+            return 0;
+        }
+        // Keeps the `width % 8` leading bits of the last byte (all of them if it's full).
+        let padding = (8 - self.width % 8) % 8;
+        let last_mask = match self.order {
+            BitOrder::MsbFirst => 0xFFu8 << padding,
+            BitOrder::LsbFirst => 0xFFu8 >> padding,
+        };
+        self.row_bytes()
+            .map(|row| {
+                let full: u32 = row[..stride - 1].iter().map(|b| b.count_ones()).sum();
+                (full + (row[stride - 1] & last_mask).count_ones()) as usize
+            })
+            .sum()
+    }
+
+    /// Yields the `(x, y)` of each set pixel of the remaining rows, in row-major order,
+    /// e.g. to draw text onto a large framebuffer touching only lit pixels. Clear bytes
+    /// are skipped whole and set bits found with `leading_zeros`, so sparse glyphs cost
+    /// far less than the per-pixel iterators.
+    pub fn set_pixels(&self) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let (width, order) = (self.width, self.order);
+        self.row_bytes().enumerate().flat_map(move |(y, row)| {
+            row.iter().enumerate().flat_map(move |(i, &byte)| {
+                // Normalized to MSB-first, with the padding past `width` masked off.
+                let byte = match order {
+                    BitOrder::MsbFirst => byte,
+                    BitOrder::LsbFirst => byte.reverse_bits(),
+                };
+                let visible = width.saturating_sub(i * 8).min(8);
+                let mut bits = byte & (0xFF00u16 >> visible) as u8;
+                core::iter::from_fn(move || {
+                    if bits == 0 {
+                        return None;
+                    }
+                    let x = bits.leading_zeros() as usize;
+                    bits &= !(0x80 >> x);
+                    Some((i * 8 + x, y))
+                })
+            })
+        })
+    }
+
+    /// The smallest rectangle holding every set pixel, as `(x, y, width, height)` with
+    /// `y` counted from the first remaining row; `None` for a blank glyph.
+    pub fn ink_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let mut pixels = self.set_pixels();
+        let (x, y) = pixels.next()?;
+        let (mut left, mut right, mut bottom) = (x, x, y);
+        // Row-major order: the first pixel has the top row, the last the bottom one.
+        for (x, y) in pixels {
+            left = left.min(x);
+            right = right.max(x);
+            bottom = y;
+        }
+        Some((left, y, right - left + 1, bottom - y + 1))
+    }
+
+    /// Yields only the [`ink_bounds`](Self::ink_bounds) of the glyph, as rows that many
+    /// pixels wide starting at its left edge, e.g. to pack sparse glyphs tightly into an
+    /// atlas. A blank glyph yields no rows.
+    pub fn cropped(&self) -> impl Iterator<Item = GlyphRow<'a>> {
+        let (x0, y0, w, h) = self.ink_bounds().unwrap_or_default();
+        self.rows_range(y0, y0 + h)
+            .map(move |row| GlyphRow::from_fn(w, |x| row.bit(x0 + x)))
+    }
+
+    /// Calls `f(x, y, on)` for every pixel of the remaining rows, in row-major order.
+    pub fn blit(&self, mut f: impl FnMut(usize, usize, bool)) {
+        for (y, row) in self.enumerate() {
+            for (x, on) in row.enumerate() {
+                f(x, y, on);
+            }
+        }
+    }
+
+    /// [`blit`](Self::blit) through a trait object, so every call site shares one copy of
+    /// the loop instead of monomorphizing it per closure; for firmware where code size
+    /// matters more than an indirect call per pixel.
+    pub fn blit_dyn(&self, f: &mut dyn FnMut(usize, usize, bool)) {
+        self.blit(f)
+    }
+
+    /// Draws the glyph as an opaque cell: `fill_bg(x0, y0, w, h)` is called once for the
+    /// whole cell (always `(0, 0, width, height)`), then `set_fg(x, y)` for each set pixel
+    /// as [`set_pixels`](Self::set_pixels) yields them, so a terminal with a background
+    /// color fills one rectangle instead of plotting every clear pixel. Coordinates are
+    /// relative to the cell's top-left corner.
+    pub fn blit_cell<F, G>(&self, mut fill_bg: F, mut set_fg: G)
+    where
+        F: FnMut(usize, usize, usize, usize),
+        G: FnMut(usize, usize),
+    {
+        let (w, h) = self.dimensions();
+        fill_bg(0, 0, w, h);
+        self.set_pixels().for_each(|(x, y)| set_fg(x, y));
+    }
+
+    /// Combines two glyphs by OR-ing their pixels row by row, e.g. to place a
+    /// combining accent or a cursor block on top of a base glyph.
+    ///
+    /// See [pairwise operations](Self#pairwise-operations) for when this is `None`.
+    pub fn overlay(&self, other: &Glyph<'a>) -> Option<impl Iterator<Item = GlyphRow<'a>>> {
+        if self.width != other.width || self.len() != other.len() {
+            return None;
+        }
+        Some(self.zip(*other).map(|(a, b)| a.combine(&b, |x, y| x | y)))
+    }
+
+    /// XORs two glyphs row by row, giving exactly the pixels that differ, e.g. to
+    /// blink a cursor by toggling only those instead of redrawing the whole cell.
+    ///
+    /// See [pairwise operations](Self#pairwise-operations) for when this is `None`.
+    pub fn xor(&self, other: &Glyph<'a>) -> Option<impl Iterator<Item = GlyphRow<'a>>> {
+        if self.width != other.width || self.len() != other.len() {
+            return None;
+        }
+        Some(self.zip(*other).map(|(a, b)| a.combine(&b, |x, y| x != y)))
+    }
+
+    /// Mixes two glyphs for a crossfade: pixels set in both are always on, and pixels
+    /// set in just one of them turn on progressively as `t` goes from 0 to 255, through a
+    /// 4×4 ordered-dither pattern so each frame is deterministic. `t = 0` gives the pixels
+    /// common to both, `t = 255` all pixels of either.
+    ///
+    /// See [pairwise operations](Self#pairwise-operations) for when this is `None`.
+    pub fn blend(&self, other: &Glyph<'a>, t: u8) -> Option<impl Iterator<Item = GlyphRow<'a>>> {
+        /// Bayer matrix: the order in which the cells of each 4×4 block turn on.
+        const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+        if self.width != other.width || self.len() != other.len() {
+            return None;
+        }
+        Some(self.zip(*other).enumerate().map(move |(y, (a, b))| {
+            GlyphRow::from_fn(a.len(), |x| {
+                let (a, b) = (a.bit(a.bit_idx + x), b.bit(b.bit_idx + x));
+                // Thresholds run from 8 to 248, so 0 lights none and 255 lights all.
+                (a && b) || ((a || b) && t >= BAYER[y & 3][x & 3] * 16 + 8)
+            })
+        }))
+    }
+
+    /// Counts the pixels that differ between two glyphs, e.g. to cluster near-identical
+    /// glyphs when deduplicating an atlas. Padding bits past `width` don't count.
+    ///
+    /// See [pairwise operations](Self#pairwise-operations) for when this is `None`.
+    pub fn hamming_distance(&self, other: &Glyph<'_>) -> Option<usize> {
+        if self.width != other.width || self.len() != other.len() {
+            return None;
+        }
+        Some(
+            self.zip(*other)
+                .map(|(a, b)| a.zip(b).filter(|(x, y)| x != y).count())
+                .sum(),
+        )
+    }
+
+    /// Centers the glyph in a `cell_w × cell_h` box, yielding `cell_h` rows of `cell_w`
+    /// pixels: blank padding where the glyph is smaller, cropped where it is larger.
+    /// E.g. to place a 5×8 icon in an 8×16 text cell.
+    ///
+    /// When the difference is odd, the extra pixel of padding (or cropping) goes on the
+    /// right and bottom. `cell_w` is [capped](Self#computed-rows).
+    pub fn fit(&self, cell_w: usize, cell_h: usize) -> impl Iterator<Item = GlyphRow<'a>> {
+        let glyph = *self;
+        let (w, h) = self.dimensions();
+        // Blank pixels before the glyph, or glyph pixels cropped off its start; at most one
+        // of each pair is non-zero. Halving rounds down, so the odd pixel is on the far side.
+        let (pad_x, crop_x) = (cell_w.saturating_sub(w) / 2, w.saturating_sub(cell_w) / 2);
+        let (pad_y, crop_y) = (cell_h.saturating_sub(h) / 2, h.saturating_sub(cell_h) / 2);
+        // The glyph coordinate shown at cell coordinate `c`, if any.
+        let source = |c: usize, pad: usize, crop: usize, size: usize| {
+            c.checked_sub(pad).map(|s| s + crop).filter(|&s| s < size)
+        };
+        (0..cell_h).map(move |y| {
+            let row = source(y, pad_y, crop_y, h).and_then(|sy| glyph.row(sy));
+            GlyphRow::from_fn(cell_w, |x| {
+                row.is_some_and(|r| source(x, pad_x, crop_x, w).is_some_and(|sx| r.bit(sx)))
+            })
+        })
+    }
+
+    /// Yields the glyph's outer edge, e.g. for a halo behind text: the clear pixels with
+    /// at least one set pixel directly above, below, left or right of them.
+    ///
+    /// The outline stays within the glyph's box, so where a stroke touches the border
+    /// the halo is cut off there. Diagonal neighbors don't count, which leaves the
+    /// outline's corners open.
+    pub fn outline(&self) -> impl Iterator<Item = GlyphRow<'a>> {
+        self.edge(false)
+    }
+
+    /// Yields the glyph's inner edge: the set pixels with at least one clear pixel
+    /// directly above, below, left or right of them. Pixels outside the glyph count as
+    /// clear, so strokes touching the border keep their edge there.
+    pub fn inner_outline(&self) -> impl Iterator<Item = GlyphRow<'a>> {
+        self.edge(true)
+    }
+
+    /// The pixels whose value is `on` and that have an orthogonal neighbor that isn't.
+    fn edge(&self, on: bool) -> impl Iterator<Item = GlyphRow<'a>> {
+        let glyph = *self;
+        let (w, h) = self.dimensions();
+        let pixel = move |x, y| glyph.pixel_at(x, y);
+        (0..h).map(move |y| {
+            GlyphRow::from_fn(w, |x| {
+                // At 0, left and up wrap to `usize::MAX`, which is outside the glyph too.
+                let neighbors = [
+                    (x, y.wrapping_sub(1)),
+                    (x, y + 1),
+                    (x.wrapping_sub(1), y),
+                    (x + 1, y),
+                ];
+                pixel(x, y) == on && neighbors.iter().any(|&(nx, ny)| pixel(nx, ny) != on)
+            })
+        })
+    }
+
+    /// ORs the glyph with a copy of itself shifted by `(dx, dy)`, for a drop shadow in
+    /// one color. Positive offsets move the shadow right and down.
+    ///
+    /// Nothing is clipped: the cell grows to `width + |dx|` by `height + |dy|`, with the
+    /// glyph pushed right or down by `|dx|`/`|dy|` when the offset is negative, so both
+    /// copies fit whole. Only the widened width is [capped](Self#computed-rows); any
+    /// offsets are accepted, down to `isize::MIN`.
+    /// For a shadow in its own color, draw these rows in that color, then the glyph
+    /// itself on top at `(max(-dx, 0), max(-dy, 0))`.
+    pub fn with_shadow(&self, dx: isize, dy: isize) -> impl Iterator<Item = GlyphRow<'a>> {
+        let glyph = *self;
+        let (w, h) = self.dimensions();
+        // Top-left corners of the glyph and its shadow in the widened cell, kept unsigned
+        // so that even `isize::MIN` offsets can't overflow.
+        let split = |d: isize| {
+            if d < 0 {
+                (d.unsigned_abs(), 0)
+            } else {
+                (0, d.unsigned_abs())
+            }
+        };
+        let ((gx, sx), (gy, sy)) = (split(dx), split(dy));
+        let pixel = move |x: usize, y: usize, (ox, oy): (usize, usize)| match (
+            x.checked_sub(ox),
+            y.checked_sub(oy),
+        ) {
+            (Some(x), Some(y)) => x < w && glyph.row(y).is_some_and(|row| row.bit(x)),
+            _ => false,
+        };
+        (0..h.saturating_add(dy.unsigned_abs())).map(move |y| {
+            GlyphRow::from_fn(w.saturating_add(dx.unsigned_abs()), |x| {
+                pixel(x, y, (gx, gy)) || pixel(x, y, (sx, sy))
+            })
+        })
+    }
+
+    /// The pixel at `(x, y)`, with everything outside the glyph clear.
+    fn pixel_at(&self, x: usize, y: usize) -> bool {
+        x < self.width && self.row(y).is_some_and(|row| row.bit(x))
+    }
+
+    /// Enlarges the glyph by an integer `factor` in both directions, each pixel becoming
+    /// a `factor × factor` block; see [`scaled_xy`](Self::scaled_xy).
+    pub fn scaled(&self, factor: NonZeroUsize) -> impl Iterator<Item = GlyphRow<'a>> {
+        self.scaled_xy(factor, factor)
+    }
+
+    /// Enlarges the glyph by `sx` horizontally and `sy` vertically, to `width * sx` by
+    /// `height * sy` pixels, e.g. doubling only one axis on a display with non-square
+    /// pixels. Rows are computed as they are yielded, and the scaled width is
+    /// [capped](Self#computed-rows).
+    pub fn scaled_xy(
+        &self,
+        sx: NonZeroUsize,
+        sy: NonZeroUsize,
+    ) -> impl Iterator<Item = GlyphRow<'a>> {
+        let (sx, sy) = (sx.get(), sy.get());
+        self.flat_map(move |row| {
+            let width = row.len().saturating_mul(sx);
+            let scaled = GlyphRow::from_fn(width, |x| row.bit(row.bit_idx + x / sx));
+            core::iter::repeat_n(scaled, sy)
+        })
+    }
+
+    /// Yields the glyph's rows emboldened the way terminals do it: every set pixel also
+    /// sets the one to its right, within the cell's `width`.
+    pub fn bold(&self) -> impl Iterator<Item = GlyphRow<'a>> {
+        self.map(|row| row.bold())
+    }
+
+    /// Yields the glyph's rows with every pixel flipped, for reverse-video cells.
+    /// Only the `width` visible columns are inverted; padding columns stay dark.
+    pub fn inverted(&self) -> impl Iterator<Item = GlyphRow<'a>> {
+        self.map(|row| row.inverted())
+    }
+
+    /// Yields the glyph's rows with the bottom row (`height - 1`) fully set.
+    ///
+    /// Spleen's descenders interact with this differently per size: at 8×16 the
+    /// bottom row is always blank, so the underline sits just below `g`/`p`/`y`.
+    /// At 5×8, 12×24 and larger sizes descenders reach the bottom row and merge
+    /// into the underline.
+    pub fn with_underline(&self) -> impl Iterator<Item = GlyphRow<'a>> {
+        self.with_line(self.len().saturating_sub(1))
+    }
+
+    /// Yields the glyph's rows with the middle row (`height / 2`) fully set.
+    pub fn with_strikethrough(&self) -> impl Iterator<Item = GlyphRow<'a>> {
+        self.with_line(self.len() / 2)
+    }
+
+    /// Substitutes an all-ones row at row `line`, passing every other row through.
+    fn with_line(&self, line: usize) -> impl Iterator<Item = GlyphRow<'a>> {
+        let width = self.width;
+        self.enumerate().map(move |(y, row)| {
+            if y == line {
+                GlyphRow::from_fn(width, |_| true)
+            } else {
+                row
+            }
+        })
     }
 }
 
 impl<'a> GlyphRow<'a> {
     pub fn new(row: &'a [u8], width: usize) -> Self {
+        Self::with_bit_order(row, width, BitOrder::MsbFirst)
+    }
+
+    /// Like [`GlyphRow::new`], for a row whose columns are packed in `order`.
+    pub fn with_bit_order(row: &'a [u8], width: usize, order: BitOrder) -> Self {
+        GlyphRow {
+            row: RowBytes::Borrowed(row),
+            bit_idx: 0,
+            width,
+            order,
+        }
+    }
+
+    /// Builds an owned row of `width` pixels where `pixel(x)` gives column `x`.
+    /// `width` is capped at `MAX_ROW_BYTES * 8`.
+    pub(crate) fn from_fn(width: usize, mut pixel: impl FnMut(usize) -> bool) -> Self {
+        let width = width.min(MAX_ROW_BYTES * 8);
+        let mut bytes = [0u8; MAX_ROW_BYTES];
+        for x in 0..width {
+            if pixel(x) {
+                bytes[x >> 3] |= 0b10000000 >> (x & 7);
+            }
+        }
         GlyphRow {
-            row,
+            row: RowBytes::Owned(bytes),
             bit_idx: 0,
             width,
+            order: BitOrder::MsbFirst,
         }
     }
+
+    /// Returns an owned copy of the remaining pixels of this row, with each set pixel
+    /// smeared one column to the right; see [`Glyph::bold`].
+    pub(crate) fn bold(&self) -> GlyphRow<'a> {
+        let start = self.bit_idx;
+        GlyphRow::from_fn(self.len(), |x| {
+            self.bit(start + x) || (x > 0 && self.bit(start + x - 1))
+        })
+    }
+
+    /// Returns an owned copy of the remaining pixels of this row, each one flipped.
+    pub fn inverted(&self) -> GlyphRow<'a> {
+        GlyphRow::from_fn(self.len(), |x| !self.bit(self.bit_idx + x))
+    }
+
+    /// Narrows the row to columns `start..end`, clamped to `0..width`, e.g. to clip a
+    /// glyph at the left or right screen edge without skipping bits by hand. Columns
+    /// are absolute, not counted from how far the row has been iterated.
+    pub fn sub(self, start: usize, end: usize) -> GlyphRow<'a> {
+        let width = end.min(self.width);
+        GlyphRow {
+            bit_idx: start.min(width),
+            width,
+            ..self
+        }
+    }
+
+    /// Yields the remaining pixels with their x-coordinate on screen, for a row drawn
+    /// with its left edge at `x0`: `(x0 + column, on)`. Columns are absolute, as in
+    /// [`sub`](Self::sub), so a clipped row keeps its pixels in place. Pixels that would
+    /// land past `usize::MAX` end the iterator.
+    pub fn cols_at(self, x0: usize) -> impl Iterator<Item = (usize, bool)> + 'a {
+        let start = self.bit_idx;
+        self.enumerate()
+            .map_while(move |(x, on)| Some((x0.checked_add(start + x)?, on)))
+    }
+
+    /// Yields the remaining pixels as `(value, length)` runs, e.g. so a blitter can
+    /// draw each set span with one `fill_rect`. Padding bits past `width` are ignored.
+    pub fn runs(self) -> impl Iterator<Item = (bool, usize)> + 'a {
+        let mut row = self;
+        core::iter::from_fn(move || {
+            let value = row.next()?;
+            let mut len = 1;
+            while row.bit_idx < row.width && row.bit(row.bit_idx) == value {
+                row.bit_idx += 1;
+                len += 1;
+            }
+            Some((value, len))
+        })
+    }
+
+    /// Combines the remaining pixels of two rows with `op`, column by column.
+    /// Columns past the end of `other` read as clear.
+    fn combine(&self, other: &GlyphRow<'_>, op: impl Fn(bool, bool) -> bool) -> GlyphRow<'a> {
+        GlyphRow::from_fn(self.len(), |x| {
+            let theirs = x < other.len() && other.bit(other.bit_idx + x);
+            op(self.bit(self.bit_idx + x), theirs)
+        })
+    }
+
+    /// The row's width in pixels, however far it has been iterated; after
+    /// [`sub`](Self::sub) that's the end column.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The packed row: `(width + 7) >> 3` bytes in the row's [`BitOrder`], padding bits
+    /// included, e.g. to send a row over a protocol that takes raw bitmaps. For a row of a
+    /// [`Glyph`] that is the row's slice of the font data. Like
+    /// [`width`](Self::width), this ignores iteration and the start column of
+    /// [`sub`](Self::sub).
+    pub fn bytes(&self) -> &[u8] {
+        let backing = self.backing();
+        backing.get(..self.width.div_ceil(8)).unwrap_or(backing)
+    }
+
+    /// All the bytes backing this row, which for computed rows is `MAX_ROW_BYTES`.
+    fn backing(&self) -> &[u8] {
+        match &self.row {
+            RowBytes::Borrowed(row) => row,
+            RowBytes::Owned(row) => row,
+        }
+    }
+
+    /// Reads the pixel at absolute column `x`, regardless of how far the iterator has advanced.
+    ///
+    /// Each row is padded to a whole number of bytes, so column `x` lives in byte `x / 8`.
+    /// Columns past the end of the bytes, as in a row built from a slice too short for
+    /// its `width`, read as clear.
+    pub(crate) fn bit(&self, x: usize) -> bool {
+        // Calculate the mask for the column; we shift by the column modulo 8.
+        let mask = match self.order {
+            BitOrder::MsbFirst => 0b10000000 >> (x & 7),
+            BitOrder::LsbFirst => 0b00000001 << (x & 7),
+        };
+        self.backing()
+            .get(x >> 3)
+            .is_some_and(|byte| byte & mask != 0)
+    }
 }
 
 impl<'a> Iterator for Glyph<'a> {
@@ -50,7 +650,7 @@ impl<'a> Iterator for Glyph<'a> {
     /// Returns the next row of the glyph.
     ///
     /// ## Example:
-    /// ```ignore
+    /// ```rust
     /// use spleen_font::Glyph;
     /// use spleen_font::GlyphRow;
     /// let glyph = Glyph::new(&[0b11001100, 0b00110011], 8);
@@ -61,33 +661,78 @@ impl<'a> Iterator for Glyph<'a> {
     /// ```
     ///
     fn next(&mut self) -> Option<Self::Item> {
-        let bytes_per_row = (self.width + 7) >> 3;
-        if self.data.len() < bytes_per_row {
-            None
-        } else {
-            let (row, rest) = self.data.split_at(bytes_per_row);
-            self.data = rest;
-            Some(GlyphRow::new(row, self.width))
+        // A zero-width glyph has no rows (height 0), not endlessly many empty ones.
+        if self.height == 0 {
+            return None;
         }
+        let (row, rest) = self.data.split_at_checked(self.stride())?;
+        self.data = rest;
+        self.height -= 1;
+        Some(GlyphRow::with_bit_order(row, self.width, self.order))
+    }
+
+    /// Exact: the rows not yet iterated.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.height();
+        (len, Some(len))
+    }
+
+    /// Skips `n` rows in one step, e.g. to clip rows above the screen.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let skipped = n.min(self.height);
+        self.data = &self.data[skipped * self.stride()..];
+        self.height -= skipped;
+        self.next()
+    }
+}
+
+/// Iterates a copy of the glyph, so `for row in &glyph` leaves `glyph` where it was
+/// and can be repeated.
+impl<'a> IntoIterator for &Glyph<'a> {
+    type Item = GlyphRow<'a>;
+    type IntoIter = Glyph<'a>;
+
+    fn into_iter(self) -> Glyph<'a> {
+        *self
+    }
+}
+
+/// Renders the glyph as ASCII art: `#` for set pixels, `.` for clear ones,
+/// one row per line. Handy with `dbg!` and in failing test output.
+///
+/// ```text
+/// .##..
+/// #..#.
+/// ####.
+/// ```
+impl core::fmt::Debug for Glyph<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for row in *self {
+            for on in row {
+                f.write_str(if on { "#" } else { "." })?;
+            }
+            f.write_str("\n")?;
+        }
+        Ok(())
     }
 }
 
 impl ExactSizeIterator for Glyph<'_> {
     fn len(&self) -> usize {
-        self.data.len() / ((self.width + 7) >> 3)
+        self.height()
     }
 }
 
 impl DoubleEndedIterator for Glyph<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        let bytes_per_row = (self.width + 7) >> 3;
-        if self.data.len() < bytes_per_row {
+        if self.height == 0 {
             return None;
         }
-        let split = self.data.len() - bytes_per_row;
-        let (rest, row) = self.data.split_at(split);
+        let split = self.data.len().checked_sub(self.stride())?;
+        let (rest, row) = self.data.split_at_checked(split)?;
         self.data = rest;
-        Some(GlyphRow::new(row, self.width))
+        self.height -= 1;
+        Some(GlyphRow::with_bit_order(row, self.width, self.order))
     }
 }
 
@@ -113,24 +758,29 @@ impl<'a> Iterator for GlyphRow<'a> {
     /// }
     /// ```
     fn next(&mut self) -> Option<Self::Item> {
+        debug_assert!(self.bit_idx <= self.width, "row iterated past its width");
         if self.bit_idx >= self.width {
             None
         } else {
-            // We bump each row width that isn't already a multiple of 8 to the next multiple of 8
-            // Then we can divide the rounded value by 8 to get the smallest whole byte count
-            // that can hold all `width` bits in a row.
-            let byte = self.row[self.bit_idx >> 3];
-
-            // Calculate the mask for the current bit index ; we shift by the bit index modulo 8.
-            let mask = 0b10000000 >> (self.bit_idx & 7);
-
             // If the bit is set, return true; otherwise, return false.
-            let bit = byte & mask != 0;
+            let bit = self.bit(self.bit_idx);
 
             self.bit_idx += 1;
             Some(bit)
         }
     }
+
+    /// Exact: the columns not yet iterated.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.width - self.bit_idx;
+        (len, Some(len))
+    }
+
+    /// Skips `n` columns in one step, e.g. to clip columns left of the screen.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.bit_idx = self.bit_idx.saturating_add(n).min(self.width);
+        self.next()
+    }
 }
 
 impl ExactSizeIterator for GlyphRow<'_> {
@@ -140,3 +790,25 @@ impl ExactSizeIterator for GlyphRow<'_> {
         self.width - self.bit_idx
     }
 }
+
+/// Rows are equal when their remaining logical pixels are equal.
+/// Padding bits past `width` are ignored, so this is not a byte comparison.
+impl PartialEq for GlyphRow<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.zip(*other).all(|(a, b)| a == b)
+    }
+}
+
+impl Eq for GlyphRow<'_> {}
+
+/// Glyphs are equal when they have the same dimensions and every row is equal;
+/// see [`GlyphRow`]'s `PartialEq` for how padding bits are treated.
+impl PartialEq for Glyph<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.len() == other.len()
+            && self.zip(*other).all(|(a, b)| a == b)
+    }
+}
+
+impl Eq for Glyph<'_> {}
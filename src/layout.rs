@@ -0,0 +1,389 @@
+//! Laying out strings as a grid of fixed-width cells, for terminal-style output.
+//!
+//! `'\n'` starts a new line and `'\t'` advances to the next tab stop; what happens to the
+//! other C0 control characters (and DEL) is up to [`ControlPolicy`]. Zero-width
+//! characters (see [`PSF2Font::advance_of`]) are drawn over the previous cell.
+
+use core::ops::RangeInclusive;
+
+use crate::cache::DEFAULT_CACHE_SIZE;
+use crate::glyph::Glyph;
+use crate::psf::PSF2Font;
+
+/// Drawn for control characters under [`ControlPolicy::Replacement`], if the font has it.
+pub const REPLACEMENT_CHAR: char = '\u{FFFD}';
+/// Drawn instead of [`REPLACEMENT_CHAR`] when the font has no glyph for that.
+pub const FALLBACK_CHAR: char = '?';
+
+/// The Unicode blocks of combining marks, the default zero-width set of a font:
+/// Combining Diacritical Marks (and their Extended and Supplement blocks), those for
+/// Symbols, and Combining Half Marks.
+pub const COMBINING_MARKS: &[RangeInclusive<char>] = &[
+    '\u{0300}'..='\u{036F}',
+    '\u{1AB0}'..='\u{1AFF}',
+    '\u{1DC0}'..='\u{1DFF}',
+    '\u{20D0}'..='\u{20FF}',
+    '\u{FE20}'..='\u{FE2F}',
+];
+
+/// What to do with control characters other than `'\n'` and `'\t'`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlPolicy {
+    /// Drop them; they take up no cell.
+    Skip,
+    /// Draw [`REPLACEMENT_CHAR`] (or [`FALLBACK_CHAR`]) in their cell.
+    Replacement,
+    /// Leave their cell blank.
+    AdvanceCell,
+}
+
+/// Digits of the bases the `render_*` number functions draw in.
+const BINARY: &[u8] = b"01";
+const DECIMAL: &[u8] = b"0123456789";
+const HEX_LOWER: &[u8] = b"0123456789abcdef";
+const HEX_UPPER: &[u8] = b"0123456789ABCDEF";
+
+/// What [`PSF2Font::render_u64_padded`] (and the hex and binary variants) fills the cells left of the digits with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// Leading zeros, e.g. `007`.
+    Zero,
+    /// The font's space glyph, right-aligning the number.
+    Space,
+}
+
+/// Options for [`PSF2Font::measure_str`] and [`PSF2Font::render_str`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutOptions {
+    /// Tab stops are every `tab_width` cells; `0` makes tabs take no space.
+    pub tab_width: usize,
+    /// Handling of the remaining control characters.
+    pub control: ControlPolicy,
+}
+
+impl Default for LayoutOptions {
+    /// Tab stops every 8 cells, control characters skipped.
+    fn default() -> Self {
+        LayoutOptions {
+            tab_width: 8,
+            control: ControlPolicy::Skip,
+        }
+    }
+}
+
+/// What ends up in a cell.
+enum Slot {
+    Char(char),
+    Blank,
+}
+
+/// A glyph positioned by [`PSF2Font::layout`], with `(x, y)` its top-left corner in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell<'a> {
+    /// Left edge: the cell's column times [`PSF2Font::cell_advance`].
+    pub x: usize,
+    /// Top edge: the cell's line times [`PSF2Font::line_height`].
+    pub y: usize,
+    /// The glyph to draw there.
+    pub glyph: Glyph<'a>,
+}
+
+/// Iterator over the positioned glyphs of a string; see [`PSF2Font::layout`].
+pub struct Layout<'t, 'a, const CACHE: usize = DEFAULT_CACHE_SIZE> {
+    font: &'t mut PSF2Font<'a, CACHE>,
+    chars: core::str::Chars<'t>,
+    options: LayoutOptions,
+    walker: CellWalker,
+}
+
+impl<'a, const CACHE: usize> Iterator for Layout<'_, 'a, CACHE> {
+    type Item = Cell<'a>;
+
+    fn next(&mut self) -> Option<Cell<'a>> {
+        loop {
+            let c = self.chars.next()?;
+            let font = &*self.font;
+            let zero_width = |c| font.advance_of(c) == 0;
+            let Some((col, line, Slot::Char(c))) = self.walker.step(c, &self.options, zero_width)
+            else {
+                continue;
+            };
+            // Glyphs borrow the font data, not the font, so the cache can be updated.
+            let glyph = match c {
+                REPLACEMENT_CHAR => self
+                    .font
+                    .glyph_for_char(c)
+                    .or_else(|| self.font.glyph_for_char(FALLBACK_CHAR)),
+                c => self.font.glyph_for_char(c),
+            };
+            if let Some(glyph) = glyph {
+                return Some(Cell {
+                    x: col * self.font.cell_advance(),
+                    y: line * self.font.line_height(),
+                    glyph,
+                });
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.chars.size_hint().1)
+    }
+}
+
+/// Position of the next cell while walking a string, shared by [`for_each_cell`] and
+/// [`Layout`] so that both place characters the same way.
+#[derive(Default)]
+struct CellWalker {
+    col: usize,
+    line: usize,
+    widest: usize,
+}
+
+impl CellWalker {
+    /// Advances past `c`, returning the `(column, line, cell)` it is drawn in or left
+    /// blank in, if any.
+    ///
+    /// Characters for which `zero_width` holds go into the previous cell, unless they start
+    /// a line, in which case they get a cell of their own so they stay visible.
+    fn step(
+        &mut self,
+        c: char,
+        options: &LayoutOptions,
+        zero_width: impl Fn(char) -> bool,
+    ) -> Option<(usize, usize, Slot)> {
+        let cell = match c {
+            '\n' => {
+                self.line += 1;
+                self.col = 0;
+                return None;
+            }
+            '\t' => {
+                if let Some(stops) = self.col.checked_div(options.tab_width) {
+                    self.col = (stops + 1) * options.tab_width;
+                }
+                None
+            }
+            c if c.is_control() && (c as u32) < 0x80 => match options.control {
+                ControlPolicy::Skip => None,
+                ControlPolicy::Replacement => Some(self.advance(Slot::Char(REPLACEMENT_CHAR))),
+                ControlPolicy::AdvanceCell => Some(self.advance(Slot::Blank)),
+            },
+            c if self.col > 0 && zero_width(c) => Some((self.col - 1, self.line, Slot::Char(c))),
+            c => Some(self.advance(Slot::Char(c))),
+        };
+        self.widest = self.widest.max(self.col);
+        cell
+    }
+
+    /// Puts `slot` in the current cell and moves on to the next.
+    fn advance(&mut self, slot: Slot) -> (usize, usize, Slot) {
+        self.col += 1;
+        (self.col - 1, self.line, slot)
+    }
+}
+
+/// Walks `text` cell by cell, calling `f(column, line, cell)` for every cell that is
+/// drawn or left blank. Returns the widest line's column count and the number of lines.
+fn for_each_cell(
+    text: &str,
+    options: &LayoutOptions,
+    zero_width: impl Fn(char) -> bool,
+    mut f: impl FnMut(usize, usize, Slot),
+) -> (usize, usize) {
+    let mut walker = CellWalker::default();
+    for c in text.chars() {
+        if let Some((col, line, cell)) = walker.step(c, options, &zero_width) {
+            f(col, line, cell);
+        }
+    }
+    (walker.widest, walker.line + 1)
+}
+
+impl<'a, const CACHE: usize> PSF2Font<'a, CACHE> {
+    /// The `(width, height)` in pixels that [`render_str`](Self::render_str) covers for `text`:
+    /// the widest line's cells by the number of lines, which are
+    /// [`line_height`](Self::line_height) apart (no gap after the last).
+    pub fn measure_str(&self, text: &str, options: &LayoutOptions) -> (usize, usize) {
+        let zero_width = |c| self.advance_of(c) == 0;
+        let (cols, lines) = for_each_cell(text, options, zero_width, |_, _, _| {});
+        (self.cells_width(cols), self.lines_height(lines))
+    }
+
+    /// Draws `value` in decimal without formatting it into a string first, e.g. for a boot
+    /// counter on a target without `alloc`. `f(cell, x, y, on)` is called for every pixel
+    /// of every digit, `cell` counting digits from the left and `(x, y)` relative to the
+    /// number's top-left corner. Returns the `(width, height)` covered.
+    pub fn render_u64(
+        &mut self,
+        value: u64,
+        f: impl FnMut(usize, usize, usize, bool),
+    ) -> (usize, usize) {
+        self.render_u64_padded(value, 0, Padding::Space, f)
+    }
+
+    /// Like [`render_u64`](Self::render_u64), but takes at least `min_width` cells,
+    /// filling the ones left of the digits with `pad`, e.g. for columns of register
+    /// values.
+    pub fn render_u64_padded(
+        &mut self,
+        value: u64,
+        min_width: usize,
+        pad: Padding,
+        f: impl FnMut(usize, usize, usize, bool),
+    ) -> (usize, usize) {
+        self.render_digits(value, DECIMAL, min_width, pad, f)
+    }
+
+    /// Draws `value` in hexadecimal, without a `0x` prefix, like
+    /// [`render_u64`](Self::render_u64); `uppercase` picks `A`–`F` over `a`–`f`.
+    pub fn render_hex(
+        &mut self,
+        value: u64,
+        uppercase: bool,
+        f: impl FnMut(usize, usize, usize, bool),
+    ) -> (usize, usize) {
+        self.render_hex_padded(value, uppercase, 0, Padding::Space, f)
+    }
+
+    /// [`render_hex`](Self::render_hex) with a minimum width; see
+    /// [`render_u64_padded`](Self::render_u64_padded).
+    pub fn render_hex_padded(
+        &mut self,
+        value: u64,
+        uppercase: bool,
+        min_width: usize,
+        pad: Padding,
+        f: impl FnMut(usize, usize, usize, bool),
+    ) -> (usize, usize) {
+        let digits = if uppercase { HEX_UPPER } else { HEX_LOWER };
+        self.render_digits(value, digits, min_width, pad, f)
+    }
+
+    /// Draws `value` in binary, without a `0b` prefix, like
+    /// [`render_u64`](Self::render_u64).
+    pub fn render_bin(
+        &mut self,
+        value: u64,
+        f: impl FnMut(usize, usize, usize, bool),
+    ) -> (usize, usize) {
+        self.render_bin_padded(value, 0, Padding::Space, f)
+    }
+
+    /// [`render_bin`](Self::render_bin) with a minimum width; see
+    /// [`render_u64_padded`](Self::render_u64_padded).
+    pub fn render_bin_padded(
+        &mut self,
+        value: u64,
+        min_width: usize,
+        pad: Padding,
+        f: impl FnMut(usize, usize, usize, bool),
+    ) -> (usize, usize) {
+        self.render_digits(value, BINARY, min_width, pad, f)
+    }
+
+    /// Draws `value` in the base of `digits`, which holds its digit characters in order.
+    fn render_digits(
+        &mut self,
+        value: u64,
+        digits: &[u8],
+        min_width: usize,
+        pad: Padding,
+        mut f: impl FnMut(usize, usize, usize, bool),
+    ) -> (usize, usize) {
+        // `u64::MAX` has 64 binary digits; they're produced last one first.
+        let radix = digits.len() as u64;
+        let (mut buf, mut len, mut rest) = ([0u8; 64], 0, value);
+        loop {
+            buf[len] = digits[(rest % radix) as usize];
+            len += 1;
+            rest /= radix;
+            if rest == 0 {
+                break;
+            }
+        }
+        let pad = match pad {
+            Padding::Zero => b'0',
+            Padding::Space => b' ',
+        };
+        let cells = len.max(min_width);
+        let padding = core::iter::repeat_n(pad, cells - len);
+        let advance = self.cell_advance();
+        for (cell, byte) in padding.chain(buf[..len].iter().rev().copied()).enumerate() {
+            let Some(glyph) = self.glyph_for_utf8(&[byte]) else {
+                continue;
+            };
+            for (y, row) in glyph.enumerate() {
+                for (x, on) in row.enumerate() {
+                    f(cell, cell * advance + x, y, on);
+                }
+            }
+        }
+        (self.cells_width(cells), self.height as usize)
+    }
+
+    /// Lays `text` out like [`render_str`](Self::render_str) with the default
+    /// [`LayoutOptions`], but yields each glyph with its position instead of drawing it,
+    /// for callers that rasterize (or cache, or hit-test) cells themselves.
+    ///
+    /// Lines are [`line_height`](Self::line_height) apart, tab stops every 8 cells and
+    /// other control characters are skipped; characters the font has no glyph for take
+    /// up a cell but yield nothing.
+    pub fn layout<'t>(&'t mut self, text: &'t str) -> Layout<'t, 'a, CACHE> {
+        Layout {
+            font: self,
+            chars: text.chars(),
+            options: LayoutOptions::default(),
+            walker: CellWalker::default(),
+        }
+    }
+
+    /// Width of `cols` adjacent cells, overlapping by the cell overlap.
+    pub(crate) fn cells_width(&self, cols: usize) -> usize {
+        match cols {
+            0 => 0,
+            cols => cols * self.cell_advance() + self.cell_overlap(),
+        }
+    }
+
+    /// Height of `lines` stacked lines, with the line gap only between them.
+    fn lines_height(&self, lines: usize) -> usize {
+        lines * self.height as usize + lines.saturating_sub(1) * self.line_gap()
+    }
+
+    /// Lays out `text` from the origin and calls `plot(x, y)` for every lit pixel,
+    /// returning the same size as [`measure_str`](Self::measure_str).
+    ///
+    /// Characters the font has no glyph for are left blank, like
+    /// [`ControlPolicy::AdvanceCell`].
+    pub fn render_str(
+        &mut self,
+        text: &str,
+        options: &LayoutOptions,
+        mut plot: impl FnMut(usize, usize),
+    ) -> (usize, usize) {
+        let (advance, pitch) = (self.cell_advance(), self.line_height());
+        // The lookups below need `&mut self`, so don't borrow it for `advance_of`.
+        let ranges = self.zero_width_ranges();
+        let zero_width = |c: char| ranges.iter().any(|range| range.contains(&c));
+        let (cols, lines) = for_each_cell(text, options, zero_width, |col, line, cell| {
+            let Slot::Char(c) = cell else {
+                return;
+            };
+            let glyph = match c {
+                REPLACEMENT_CHAR => self
+                    .glyph_for_char(c)
+                    .or_else(|| self.glyph_for_char(FALLBACK_CHAR)),
+                c => self.glyph_for_char(c),
+            };
+            for (y, row) in glyph.into_iter().flatten().enumerate() {
+                for (x, on) in row.enumerate() {
+                    if on {
+                        plot(col * advance + x, line * pitch + y);
+                    }
+                }
+            }
+        });
+        (self.cells_width(cols), self.lines_height(lines))
+    }
+}
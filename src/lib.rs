@@ -22,20 +22,18 @@
 //! This example assumes that you have a framebuffer and a function to set pixels.
 //!
 //! ```rust
-//! fn main() {
-//!     // only compiled & run when `s8x16` is enabled
-//!     #[cfg(feature = "s8x16")] {
-//!         use spleen_font::{PSF2Font, FONT_8X16};
-//!         fn set_pixel(_: &mut [u8], _: usize, _: usize, _: bool) { }
-//!
-//!         let mut font       = PSF2Font::new(FONT_8X16).unwrap();
-//!         let mut framebuffer = [0u8; 1024];
-//!
-//!         if let Some(glyph) = font.glyph_for_utf8("é".as_bytes()) {
-//!             for (row_y, row) in glyph.enumerate() {
-//!                 for (col_x, on) in row.enumerate() {
-//!                     set_pixel(&mut framebuffer, col_x, row_y, on);
-//!                 }
+//! // only compiled & run when `s8x16` is enabled
+//! #[cfg(feature = "s8x16")] {
+//!     use spleen_font::{PSF2Font, FONT_8X16};
+//!     fn set_pixel(_: &mut [u8], _: usize, _: usize, _: bool) { }
+//!
+//!     let mut font       = PSF2Font::new(FONT_8X16).unwrap();
+//!     let mut framebuffer = [0u8; 1024];
+//!
+//!     if let Some(glyph) = font.glyph_for_utf8("é".as_bytes()) {
+//!         for (row_y, row) in glyph.enumerate() {
+//!             for (col_x, on) in row.enumerate() {
+//!                 set_pixel(&mut framebuffer, col_x, row_y, on);
 //!             }
 //!         }
 //!     }
@@ -48,6 +46,16 @@
 //! cargo test --doc --features s8x16
 //! ```
 //!
+//! ## Panics
+//!
+//! No input makes parsing, lookups or glyph iteration panic: [`PSF2Font::new`] and the
+//! other constructors reject malformed fonts with a [`PsfError`], and the lookup and row
+//! and pixel iterators index with `get` and checked arithmetic, returning `None` (or
+//! clear pixels) instead. Sizes and coordinates passed near `usize::MAX` saturate, are
+//! capped or end the iterator early. The exceptions are documented under `# Panics`, like
+//! [`PSF2Font::new_const`], and `debug_assert!`s of internal invariants, which are
+//! compiled out of release builds.
+//!
 //! ## Feature gates
 //!
 //! To reduce the overall footprint, each of the six fonts is gated behind a feature. Note that while this does not reduce the size of the crate on crates.io, it does reduce the size of the compiled binary - only the blob for the selected font you enable will be included in the final binary.
@@ -62,6 +70,21 @@
 //! s32x64 | FONT_32X64 | 128 KiB
 //! all | all of the above | 184 KiB
 //!
+//! The `ascii-only` feature makes [`PSF2Font`] ignore unicode tables entirely: lookups
+//! resolve through the ASCII fast path only and return `None` for anything above `0x7F`.
+//! This drops the table-scanning code from the binary, but not the table bytes inside
+//! the embedded `.psfu` blobs; pair it with [`PSF2Font::subset`] in a build script to
+//! shrink those too.
+//!
+//! The `sync` feature adds [`SyncPSF2Font`], whose lookups take `&self` so one parsed
+//! font can be shared between threads or cores; see [`sync`] for the locking tradeoff.
+//!
+//! The `raster` feature adds glyph helpers that render into byte-per-pixel buffers,
+//! such as `Glyph::downscale_2x_gray`.
+//!
+//! The `alloc` feature adds `Glyph::to_vec` and `PSF2Font::rasterize_str_to_vec`, which
+//! return owned rasters; the rest of the crate never allocates.
+//!
 //! Each entry is a raw byte slice **`&[u8]`** where the slice is the raw PSF-2 file embedded via `include_bytes!`.
 //!
 //! By default, no font is enabled. Enabling a font in your Cargo.toml should look like this:
@@ -75,6 +98,9 @@
 //!
 //! * [`PSF2Font`] — loader + glyph/Unicode lookup.
 //! * [`Glyph`] / [`GlyphRow`] — iterators over rows and pixels.
+//! * [`PSF2Writer`] — serializes glyph bitmaps back out to PSF2.
+//! * [`FontBuilder`] — assembles a custom font from glyphs and characters.
+//! * [`PsfError`] — why a font couldn't be parsed or written.
 //!
 //! ## Bundled fonts
 //!
@@ -90,6 +116,9 @@
 //! Each entry is a tuple **`(&[u8], Size)`** where the slice is the raw PSF-2
 //! file embedded via `include_bytes!`.
 //!
+//! To pick a font by name rather than by index, use [`font_bytes`] or [`load`]; both
+//! return `None` for sizes whose feature isn't enabled.
+//!
 //! ---
 //!
 //! *Spleen font Copyright (c) 2018-2024, Frederic Cambus, BSD2 License*
@@ -98,14 +127,40 @@
 
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod builder;
 mod cache;
+pub mod chain;
+pub mod error;
 pub mod glyph;
+pub mod layout;
+#[cfg(feature = "alloc")]
+mod owned;
 pub mod psf;
+#[cfg(feature = "raster")]
+mod raster;
+pub mod style;
+#[cfg(feature = "sync")]
+pub mod sync;
+pub mod utf8;
+pub mod writer;
 
-pub use glyph::{Glyph, GlyphRow};
-pub use psf::{PSF2Font, PSF2Header, PSF2_MAGIC};
+pub use builder::FontBuilder;
+pub use cache::{CacheStats, DEFAULT_CACHE_SIZE};
+pub use chain::FontChain;
+pub use error::{LookupMiss, PsfError};
+pub use glyph::{BitOrder, Glyph, GlyphRow};
+pub use layout::{Cell, ControlPolicy, Layout, LayoutOptions, Padding};
+pub use psf::{Glyphs, OobPolicy, PSF2Font, PSF2Header, UnicodeTableIter, PSF2_MAGIC};
+pub use style::{Attrs, StyledGlyph};
+#[cfg(feature = "sync")]
+pub use sync::SyncPSF2Font;
+pub use writer::PSF2Writer;
 
 /// Logical name for each embedded Spleen size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Size {
     S5x8,
     S6x12,
@@ -115,6 +170,95 @@ pub enum Size {
     S32x64,
 }
 
+impl Size {
+    /// Every size, smallest first; a size's position here is its index in `FONTS`.
+    pub const ALL: [Size; 6] = [
+        Size::S5x8,
+        Size::S6x12,
+        Size::S8x16,
+        Size::S12x24,
+        Size::S16x32,
+        Size::S32x64,
+    ];
+
+    /// Number of sizes, i.e. `Size::ALL.len()`.
+    pub const fn count() -> usize {
+        Size::ALL.len()
+    }
+
+    /// Glyph `(width, height)` in pixels.
+    pub const fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Size::S5x8 => (5, 8),
+            Size::S6x12 => (6, 12),
+            Size::S8x16 => (8, 16),
+            Size::S12x24 => (12, 24),
+            Size::S16x32 => (16, 32),
+            Size::S32x64 => (32, 64),
+        }
+    }
+
+    /// Number of glyphs in the bundled font of this size, known without parsing it, e.g.
+    /// to size an atlas array at compile time.
+    pub const fn num_glyphs(&self) -> u32 {
+        match self {
+            Size::S5x8 => 512,
+            Size::S6x12 => 512,
+            Size::S8x16 => 512,
+            Size::S12x24 => 512,
+            Size::S16x32 => 512,
+            Size::S32x64 => 512,
+        }
+    }
+}
+
+/// Formats as `"<width>x<height>"`, e.g. `8x16`.
+impl core::fmt::Display for Size {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (width, height) = self.dimensions();
+        write!(f, "{}x{}", width, height)
+    }
+}
+
+/// Parses `"<width>x<height>"` (e.g. from a `font=8x16` kernel argument), ignoring
+/// surrounding whitespace.
+impl core::str::FromStr for Size {
+    type Err = PsfError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height) = s.trim().split_once('x').ok_or(PsfError::UnknownSize)?;
+        let width = width.parse().map_err(|_| PsfError::UnknownSize)?;
+        let height = height.parse().map_err(|_| PsfError::UnknownSize)?;
+        Size::try_from((width, height))
+    }
+}
+
+/// Looks a size up by glyph `(width, height)`.
+impl TryFrom<(u32, u32)> for Size {
+    type Error = PsfError;
+
+    fn try_from(dimensions: (u32, u32)) -> Result<Self, Self::Error> {
+        Ok(match dimensions {
+            (5, 8) => Size::S5x8,
+            (6, 12) => Size::S6x12,
+            (8, 16) => Size::S8x16,
+            (12, 24) => Size::S12x24,
+            (16, 32) => Size::S16x32,
+            (32, 64) => Size::S32x64,
+            _ => return Err(PsfError::UnknownSize),
+        })
+    }
+}
+
+/// Looks a size up by its position in [`Size::ALL`] (and `FONTS`).
+impl TryFrom<usize> for Size {
+    type Error = PsfError;
+
+    fn try_from(index: usize) -> Result<Self, Self::Error> {
+        Size::ALL.get(index).copied().ok_or(PsfError::UnknownSize)
+    }
+}
+
 #[cfg(feature = "s5x8")]
 pub const FONT_5X8: &[u8] = include_bytes!("../fonts/spleen-5x8.psfu");
 #[cfg(feature = "s6x12")]
@@ -128,7 +272,7 @@ pub const FONT_16X32: &[u8] = include_bytes!("../fonts/spleen-16x32.psfu");
 #[cfg(feature = "s32x64")]
 pub const FONT_32X64: &[u8] = include_bytes!("../fonts/spleen-32x64.psfu");
 
-#[cfg(feature = "all")]
+#[cfg(any(feature = "all", test))]
 pub static FONTS: &[(&[u8], Size)] = &[
     (include_bytes!("../fonts/spleen-5x8.psfu"), Size::S5x8),
     (include_bytes!("../fonts/spleen-6x12.psfu"), Size::S6x12),
@@ -138,26 +282,84 @@ pub static FONTS: &[(&[u8], Size)] = &[
     (include_bytes!("../fonts/spleen-32x64.psfu"), Size::S32x64),
 ];
 
-#[cfg(test)]
-pub static FONTS: &[(&[u8], Size)] = &[
-    (include_bytes!("../fonts/spleen-5x8.psfu"), Size::S5x8),
-    (include_bytes!("../fonts/spleen-6x12.psfu"), Size::S6x12),
-    (include_bytes!("../fonts/spleen-8x16.psfu"), Size::S8x16),
-    (include_bytes!("../fonts/spleen-12x24.psfu"), Size::S12x24),
-    (include_bytes!("../fonts/spleen-16x32.psfu"), Size::S16x32),
-    (include_bytes!("../fonts/spleen-32x64.psfu"), Size::S32x64),
-];
+/// Returns the embedded blob for `size`, or `None` if its feature isn't enabled.
+pub fn font_bytes(size: Size) -> Option<&'static [u8]> {
+    match size {
+        #[cfg(feature = "s5x8")]
+        Size::S5x8 => Some(FONT_5X8),
+        #[cfg(feature = "s6x12")]
+        Size::S6x12 => Some(FONT_6X12),
+        #[cfg(feature = "s8x16")]
+        Size::S8x16 => Some(FONT_8X16),
+        #[cfg(feature = "s12x24")]
+        Size::S12x24 => Some(FONT_12X24),
+        #[cfg(feature = "s16x32")]
+        Size::S16x32 => Some(FONT_16X32),
+        #[cfg(feature = "s32x64")]
+        Size::S32x64 => Some(FONT_32X64),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// Parses the embedded font for `size`, or returns `None` if its feature isn't enabled.
+pub fn load(size: Size) -> Option<PSF2Font<'static>> {
+    PSF2Font::new(font_bytes(size)?).ok()
+}
 
 #[cfg(test)]
 extern crate std;
 
 #[cfg(test)]
-use std::vec::Vec;
+use std::{format, vec::Vec};
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    /// A glyph's rows as ASCII art, `#` for set pixels and `.` for clear ones.
+    fn render<'a>(rows: impl Iterator<Item = GlyphRow<'a>>) -> Vec<std::string::String> {
+        rows.map(|row| row.map(|on| if on { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    /// A glyph's rows as pixels, for comparing regions of them.
+    fn pixels<'a>(rows: impl Iterator<Item = GlyphRow<'a>>) -> Vec<Vec<bool>> {
+        rows.map(|row| row.collect()).collect()
+    }
+
+    /// Assembles a PSF2 blob in memory. Glyph `i` is filled with the byte `i`
+    /// so lookups can be checked by inspecting the returned data.
+    fn synthetic_font(
+        header_size: u32,
+        flags: u32,
+        num_glyphs: u32,
+        width: u32,
+        height: u32,
+        table: &[u8],
+    ) -> Vec<u8> {
+        let bytes_per_glyph = ((width + 7) >> 3) * height;
+        let mut blob = Vec::new();
+        blob.extend_from_slice(&PSF2_MAGIC);
+        for field in [
+            0,
+            header_size,
+            flags,
+            num_glyphs,
+            bytes_per_glyph,
+            height,
+            width,
+        ] {
+            blob.extend_from_slice(&field.to_le_bytes());
+        }
+        blob.resize(header_size as usize, 0);
+        for idx in 0..num_glyphs {
+            blob.extend((0..bytes_per_glyph).map(|_| idx as u8));
+        }
+        blob.extend_from_slice(table);
+        blob
+    }
+
     #[test]
     fn header_round_trip() {
         let (blob, _) = FONTS[1]; // 8×16 face
@@ -177,6 +379,22 @@ mod test {
         assert!(PSF2Header::from_bytes(&bad).is_err());
     }
 
+    #[test]
+    fn new_keeps_psf2_header_errors() {
+        let mut versioned = FONTS[0].0.to_vec();
+        versioned[4] = 1;
+        assert_eq!(
+            PSF2Font::new(&versioned).err(),
+            Some(PsfError::UnsupportedVersion)
+        );
+        assert_eq!(
+            PSF2Font::new(&FONTS[0].0[..20]).err(),
+            Some(PsfError::HeaderTooShort)
+        );
+        // Without either magic it's still just not a font.
+        assert_eq!(PSF2Font::new(&[0; 20]).err(), Some(PsfError::InvalidMagic));
+    }
+
     #[test]
     fn open_font_and_first_last_glyph() {
         let (blob, _) = FONTS[1]; // 6×12
@@ -200,14 +418,15 @@ mod test {
         let mut font = PSF2Font::new(blob).unwrap();
 
         // first call -> cold   (fills cache)
-        let g1 = font.get_glyph_data(&[b'A']).unwrap();
+        let g1 = font.get_glyph_data(b"A").unwrap();
         // second call -> cache hit
-        let g2 = font.get_glyph_data(&[b'A']).unwrap();
+        let g2 = font.get_glyph_data(b"A").unwrap();
         assert!(core::ptr::eq(g1.as_ptr(), g2.as_ptr()));
     }
 
     // Unicode lookup (é)  & iterator correctness
     #[test]
+    #[cfg(not(feature = "ascii-only"))]
     fn unicode_lookup_and_iter() {
         let (blob, _) = FONTS[0]; // 5x8
         let mut font = PSF2Font::new(blob).unwrap();
@@ -220,15 +439,2701 @@ mod test {
         assert_eq!(glyph.len(), font.height as usize);
 
         // Every row must yield exactly width bits
-        for row in glyph.clone() {
+        for row in glyph {
             assert_eq!(row.len(), font.width as usize);
         }
 
         // Forward → collect() then reverse → collect_rev() must match
-        let forward: Vec<bool> = glyph.clone().flat_map(|r| r).collect();
-        let reverse: Vec<bool> = glyph.rev().flat_map(|r| r).collect();
+        let forward: Vec<bool> = glyph.flatten().collect();
+        let reverse: Vec<bool> = glyph.rev().flatten().collect();
         let mut rev2 = reverse.clone();
         rev2.reverse();
         assert_eq!(forward, rev2);
     }
+
+    // Fonts without a unicode table map codepoints straight to glyph indices.
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn no_unicode_table_direct_mapping() {
+        let blob = synthetic_font(32, 0, 256, 8, 8, &[]);
+        let mut font = PSF2Font::new(&blob).unwrap();
+
+        assert_eq!(font.get_glyph_data(b"A").unwrap()[0], b'A');
+        assert_eq!(font.get_glyph_data("é".as_bytes()).unwrap()[0], 0xE9);
+        assert_eq!(font.glyph_for_utf8("ÿ".as_bytes()).unwrap().len(), 8);
+
+        // Codepoints past num_glyphs and malformed input have no glyph.
+        assert!(font.get_glyph_data("Ā".as_bytes()).is_none());
+        assert!(font.get_glyph_data(&[0xC3]).is_none());
+        assert!(font.get_glyph_data("éé".as_bytes()).is_none());
+    }
+
+    // Extended headers: the bitmaps start at `header_size`, not at byte 32.
+    #[test]
+    fn padded_header_offsets_glyphs() {
+        let blob = synthetic_font(48, 0, 128, 8, 8, &[]);
+        let font = PSF2Font::new(&blob).unwrap();
+
+        assert_eq!(font.header_size, 48);
+        assert_eq!(font.glyph_by_idx(0).unwrap().as_ptr(), blob[48..].as_ptr());
+        assert_eq!(font.glyph_by_idx(1).unwrap(), &[1; 8]);
+    }
+
+    #[test]
+    fn header_size_out_of_range_is_rejected() {
+        let mut short = synthetic_font(32, 0, 1, 8, 8, &[]);
+        short[8..12].copy_from_slice(&16u32.to_le_bytes());
+        assert!(PSF2Font::new(&short).is_err());
+
+        let mut long = synthetic_font(32, 0, 1, 8, 8, &[]);
+        long[8..12].copy_from_slice(&4096u32.to_le_bytes());
+        assert!(PSF2Font::new(&long).is_err());
+    }
+
+    #[test]
+    fn psf1_header_reads_mode_then_charsize() {
+        // 512 glyphs of 8 bytes; read the other way round it would be 256 glyphs of 1.
+        let mut psf1 = std::vec![0x36, 0x04, 0x01, 8];
+        psf1.resize(4 + 512 * 8, 0xAA);
+        let font = PSF2Font::new(&psf1).unwrap();
+        assert_eq!((font.num_glyphs, font.bytes_per_glyph), (512, 8));
+        assert_eq!((font.width, font.height), (8, 8));
+        assert!(!font.has_unicode_table());
+
+        let (blob, _) = FONTS[2];
+        let font = PSF2Font::new(blob).unwrap();
+        assert_eq!((blob[2], blob[3]), (0x02 | 0x01, 16));
+        assert_eq!((font.num_glyphs, font.bytes_per_glyph), (512, 16));
+
+        // A table with sequences is a table, even without the plain table bit.
+        let mut psf1 = std::vec![0x36, 0x04, 0x04, 1];
+        psf1.extend(0..=255u8);
+        for glyph in 0..256u16 {
+            psf1.extend_from_slice(&(0x100 + glyph).to_le_bytes());
+            psf1.extend_from_slice(&[0xFE, 0xFF, b'x', 0, 0xFF, 0xFF]);
+        }
+        let mut font = PSF2Font::new(&psf1).unwrap();
+        if !cfg!(feature = "ascii-only") {
+            assert!(font.has_unicode_table());
+            assert_eq!(font.get_glyph_data("ā".as_bytes()), Some(&[1u8][..]));
+        }
+    }
+
+    // Header parsing and font construction are usable in `const` items.
+    #[test]
+    fn const_font_parsing() {
+        const BLOB: &[u8] = include_bytes!("../fonts/spleen-8x16.psfu");
+        const FONT: PSF2Font = PSF2Font::new_const(BLOB);
+        const HEADER: Result<PSF2Header, PsfError> =
+            PSF2Header::from_bytes(include_bytes!("../fonts/spleen-16x32.psfu"));
+
+        assert_eq!((FONT.width, FONT.height), (8, 16));
+        assert_eq!(HEADER.unwrap().width, 16);
+
+        let mut font = FONT;
+        assert!(font.glyph_for_utf8(b"A").is_some());
+    }
+
+    // Equality compares visible pixels only; padding bits past `width` don't count.
+    #[test]
+    fn glyph_eq_ignores_padding_bits() {
+        let (blob, _) = FONTS[0]; // 5x8
+        let mut font = PSF2Font::new(blob).unwrap();
+        let data = font.get_glyph_data(b"A").unwrap();
+        let glyph = Glyph::new(data, 5);
+
+        // Low 3 bits of each row byte are padding for a 5-wide glyph.
+        let padded: Vec<u8> = data.iter().map(|b| b | 0b0000_0111).collect();
+        assert_eq!(glyph, Glyph::new(&padded, 5));
+        assert_eq!(
+            GlyphRow::new(&[0b1010_1000], 5),
+            GlyphRow::new(&[0b1010_1111], 5)
+        );
+
+        // Flipping a visible pixel breaks equality.
+        let mut changed = data.to_vec();
+        changed[0] ^= 0b1000_0000;
+        assert_ne!(glyph, Glyph::new(&changed, 5));
+
+        // Different widths or heights never compare equal.
+        assert_ne!(glyph, Glyph::new(data, 8));
+        assert_ne!(glyph, Glyph::new(&data[..7], 5));
+    }
+
+    #[test]
+    fn debug_renders_ascii_art() {
+        let glyph = Glyph::new(&[0b1010_0000, 0b0101_1111, 0b1110_0000], 3);
+        assert_eq!(format!("{:?}", glyph), "#.#\n.#.\n###\n");
+
+        // Every row of a real glyph is exactly `width` characters wide.
+        let (blob, _) = FONTS[3]; // 12x24
+        let mut font = PSF2Font::new(blob).unwrap();
+        let art = format!("{:?}", font.glyph_for_utf8(b"M").unwrap());
+        assert_eq!(art.lines().count(), 24);
+        assert!(art.lines().all(|line| line.len() == 12));
+        assert!(art.contains('#'));
+    }
+
+    // Compose a base glyph with a combining-tilde-shaped overlay.
+    #[test]
+    fn overlay_ors_rows() {
+        let (blob, _) = FONTS[2]; // 8x16
+        let mut font = PSF2Font::new(blob).unwrap();
+        let base = font.glyph_for_utf8(b"n").unwrap();
+
+        let mut tilde = [0u8; 16];
+        tilde[1] = 0b0011_0010;
+        tilde[2] = 0b0100_1100;
+        let accent = Glyph::new(&tilde, 8);
+
+        let expected: Vec<u8> = font
+            .get_glyph_data(b"n")
+            .unwrap()
+            .iter()
+            .zip(tilde)
+            .map(|(a, b)| a | b)
+            .collect();
+
+        let combined: Vec<GlyphRow> = base.overlay(&accent).unwrap().collect();
+        let reference: Vec<GlyphRow> = Glyph::new(&expected, 8).collect();
+        assert_eq!(combined, reference);
+
+        // Overlaying with a blank glyph is the identity.
+        let blank = Glyph::new(&[0; 16], 8);
+        assert!(base.overlay(&blank).unwrap().eq(base));
+
+        // Mismatched dimensions are rejected.
+        assert!(base.overlay(&Glyph::new(&tilde[..8], 8)).is_none());
+        assert!(base.overlay(&Glyph::new(&tilde, 5)).is_none());
+    }
+
+    #[test]
+    fn inverted_round_trips_and_respects_width() {
+        let (blob, _) = FONTS[0]; // 5x8, so every row has 3 padding bits
+        let mut font = PSF2Font::new(blob).unwrap();
+        let glyph = font.glyph_for_utf8(b"g").unwrap();
+
+        for (orig, inv) in glyph.zip(glyph.inverted()) {
+            assert_eq!(inv.len(), 5);
+            assert!(orig.zip(inv).all(|(a, b)| a != b));
+        }
+        assert!(glyph.inverted().map(|row| row.inverted()).eq(glyph));
+
+        // A blank row inverts to exactly `width` set pixels, never more.
+        let lit: Vec<bool> = GlyphRow::new(&[0], 5).inverted().collect();
+        assert_eq!(lit, [true; 5]);
+    }
+
+    #[test]
+    fn underline_and_strikethrough_rows() {
+        // (font index, underline row, strikethrough row)
+        for (idx, under, strike) in [(2, 15, 8), (4, 31, 16)] {
+            let (blob, _) = FONTS[idx];
+            let mut font = PSF2Font::new(blob).unwrap();
+            let glyph = font.glyph_for_utf8(b"x").unwrap();
+            let width = font.width as usize;
+
+            for (decorated, line) in [
+                (glyph.with_underline().collect::<Vec<_>>(), under),
+                (glyph.with_strikethrough().collect::<Vec<_>>(), strike),
+            ] {
+                assert_eq!(decorated.len(), glyph.len());
+                for (y, (row, orig)) in decorated.into_iter().zip(glyph).enumerate() {
+                    if y == line {
+                        assert_eq!(row.len(), width);
+                        assert!(row.into_iter().all(|on| on));
+                    } else {
+                        assert_eq!(row, orig);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn ascii_fast_path_needs_an_identity_table() {
+        // Only glyph 0 is listed, as 'A'; the fast path would hand out glyph 0x41.
+        let mut table = std::vec![b'A', 0xFF];
+        table.resize(129, 0xFF);
+        let blob = synthetic_font(32, 1, 128, 8, 1, &table);
+        let mut font = PSF2Font::new(&blob).unwrap();
+        assert_eq!(font.get_glyph_data(b"A"), Some(&[0u8][..]));
+        assert_eq!(font.get_glyph_data(b"B"), None);
+
+        let table: Vec<u8> = (0..128u8).flat_map(|b| [b, 0xFF]).collect();
+        let blob = synthetic_font(32, 1, 128, 8, 1, &table);
+        let mut font = PSF2Font::new(&blob).unwrap();
+        assert_eq!(font.get_glyph_data(b"B"), Some(&[b'B'][..]));
+    }
+
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn scanner_splits_records_into_entries() {
+        // Glyph 0 lists four codepoints; glyph 1 only the sequence "ab".
+        let mut table = std::vec![b'a', b'b'];
+        table.extend_from_slice("é€".as_bytes());
+        table.extend_from_slice(&[0xFF, 0xFE, b'a', b'b', 0xFF]);
+        let blob = synthetic_font(32, 1, 2, 8, 1, &table);
+        let mut font = PSF2Font::new(&blob).unwrap();
+        for entry in ["a", "b", "é", "€"] {
+            assert_eq!(
+                font.get_glyph_data(entry.as_bytes()),
+                Some(&[0u8][..]),
+                "{entry}"
+            );
+        }
+        assert_eq!(font.get_glyph_data(b"ab"), Some(&[1u8][..]));
+        assert_eq!(font.get_glyph_data("é€".as_bytes()), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn writer_round_trips_through_parser() {
+        let bitmaps = [0xFF, 0x00, 0x00, 0xFF, 0x18, 0x18, 0x18, 0x18];
+        let map = [('A', 0), ('é', 1), ('a', 0)];
+        let writer = PSF2Writer::new(8, 4, &bitmaps).with_unicode(&map);
+
+        let mut buf = [0u8; 64];
+        let len = writer.write(&mut buf).unwrap();
+        assert_eq!(len, writer.encoded_len());
+        assert_eq!(&buf[len - 6..len], &[b'A', b'a', 0xFF, 0xC3, 0xA9, 0xFF]);
+
+        let mut font = PSF2Font::new(&buf[..len]).unwrap();
+        assert_eq!((font.width, font.height, font.num_glyphs), (8, 4, 2));
+        assert_eq!(font.get_glyph_data(b"A"), Some(&bitmaps[..4]));
+        assert_eq!(font.get_glyph_data(b"a"), Some(&bitmaps[..4]));
+        assert_eq!(font.get_glyph_data("é".as_bytes()), Some(&bitmaps[4..]));
+        assert_eq!(font.get_glyph_data(b"b"), None);
+    }
+
+    #[test]
+    fn writer_rejects_bad_input() {
+        let bitmaps = [0xAA; 8];
+        let mut buf = [0u8; 64];
+
+        assert!(PSF2Writer::new(8, 3, &bitmaps).write(&mut buf).is_err());
+        assert!(PSF2Writer::new(8, 4, &bitmaps)
+            .with_unicode(&[('x', 2)])
+            .write(&mut buf)
+            .is_err());
+        assert!(PSF2Writer::new(8, 4, &bitmaps)
+            .write(&mut buf[..39])
+            .is_err());
+
+        for (width, height) in [(u32::MAX, 9), (16, u32::MAX)] {
+            let huge = PSF2Writer::new(width, height, &bitmaps);
+            assert_eq!(huge.bytes_per_glyph(), u32::MAX);
+            assert_eq!(huge.num_glyphs(), 0);
+            assert_eq!(huge.write(&mut buf), Err(PsfError::InvalidDimensions));
+        }
+
+        // Without a unicode table the blob is just header + bitmaps.
+        let len = PSF2Writer::new(8, 4, &bitmaps).write(&mut buf).unwrap();
+        assert_eq!(len, 40);
+        assert_eq!(PSF2Font::new(&buf[..len]).unwrap().num_glyphs, 2);
+    }
+
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn unicode_record_entries_match_individually() {
+        let (blob, _) = FONTS[3]; // 12x24
+        let mut font = PSF2Font::new(blob).unwrap();
+
+        // Latin A, Cyrillic А and Greek Α share one record with Ⓐ.
+        let a = font.get_glyph_data(b"A").unwrap();
+        for alias in ["А", "Α", "Ⓐ"] {
+            let glyph = font.get_glyph_data(alias.as_bytes()).unwrap();
+            assert!(core::ptr::eq(a.as_ptr(), glyph.as_ptr()));
+        }
+        assert!(font.get_glyph_data("AА".as_bytes()).is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn builder_round_trip_with_dedup() {
+        let a = [0x18, 0x24, 0x3C, 0x24];
+        let b = [0x38, 0x24, 0x38, 0x3C];
+        // Same pixels as `a` in a 7-wide font once padding is ignored.
+        let a_padded = [0x19, 0x25, 0x3D, 0x25];
+
+        let mut buf = [0u8; 128];
+        let builder = FontBuilder::<4>::with_capacity(7, 4)
+            .dedup(true)
+            .add_glyph(&a, &['A', 'a'])
+            .add_glyph(&b, &['B'])
+            .add_glyph(&a_padded, &['À']);
+        assert_eq!(builder.num_glyphs(), 2);
+
+        let len = builder.build(&mut buf).unwrap();
+        assert_eq!(len, builder.encoded_len());
+        let mut font = PSF2Font::new(&buf[..len]).unwrap();
+        assert_eq!((font.width, font.height, font.num_glyphs), (7, 4, 2));
+        assert_eq!(font.get_glyph_data(b"a"), Some(&a[..]));
+        assert_eq!(font.get_glyph_data(b"B"), Some(&b[..]));
+        assert_eq!(font.get_glyph_data("À".as_bytes()), Some(&a[..]));
+
+        // Without dedup every bitmap gets its own index.
+        let plain = FontBuilder::new(7, 4)
+            .add_glyph(&a, &['A'])
+            .add_glyph(&a_padded, &['À']);
+        let mut font = plain.build_font(&mut buf).unwrap();
+        assert_eq!(font.num_glyphs, 2);
+        assert_eq!(font.get_glyph_data("À".as_bytes()), Some(&a_padded[..]));
+    }
+
+    #[test]
+    fn builder_reports_errors_on_build() {
+        let glyph = [0u8; 4];
+        let mut buf = [0u8; 128];
+
+        let full = FontBuilder::<1>::with_capacity(8, 4)
+            .add_glyph(&glyph, &['x'])
+            .add_glyph(&glyph, &['y']);
+        assert!(full.build(&mut buf).is_err());
+
+        let wrong_size = FontBuilder::new(8, 4).add_glyph(&glyph[..3], &['x']);
+        assert!(wrong_size.build(&mut buf).is_err());
+
+        let ok = FontBuilder::new(8, 4).add_glyph(&glyph, &['x']);
+        assert!(ok.build(&mut buf[..ok.encoded_len() - 1]).is_err());
+
+        // Glyphs too large for the header's 32-bit `bytes_per_glyph` field.
+        for (width, height) in [(u32::MAX, 9), (16, u32::MAX)] {
+            let huge = FontBuilder::new(width, height).add_glyph(&glyph, &['x']);
+            assert_eq!(huge.bytes_per_glyph(), u32::MAX);
+            assert_eq!(
+                huge.build(&mut buf).err(),
+                Some(PsfError::InvalidDimensions)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn subset_keeps_only_requested_glyphs() {
+        let (blob, _) = FONTS[3]; // 12x24
+        let mut font = PSF2Font::new(blob).unwrap();
+        let chars = ['é', 'A', '€', 'A', 'А', '\u{10FFFF}'];
+
+        let mut buf = [0u8; 512];
+        let len = font.subset(&chars, &mut buf).unwrap();
+        let mut small = PSF2Font::new(&buf[..len]).unwrap();
+
+        // Glyph 0 plus one glyph each for é, €, and A/А (which share a glyph).
+        assert_eq!(small.num_glyphs, 4);
+        assert_eq!(small.glyph_by_idx(0), font.glyph_by_idx(0));
+        for c in ['é', 'A', '€', 'А'] {
+            let mut tmp = [0; 4];
+            let utf8 = c.encode_utf8(&mut tmp).as_bytes();
+            let original = font.get_glyph_data(utf8).unwrap();
+            assert_eq!(small.get_glyph_data(utf8), Some(original));
+        }
+        assert!(small.get_glyph_data(b"B").is_none());
+        assert!(small.get_glyph_data("\u{10FFFF}".as_bytes()).is_none());
+
+        assert_eq!(
+            font.subset(&chars, &mut buf[..len - 1]),
+            Err(PsfError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn subset_of_psf1_font_keeps_its_glyphs() {
+        let (blob, _) = FONTS[2]; // 8x16, PSF1 with a UCS-2 table
+        let mut font = PSF2Font::new(blob).unwrap();
+
+        let mut buf = [0u8; 256];
+        let len = font.subset(&['A', 'é'], &mut buf).unwrap();
+        let mut small = PSF2Font::new(&buf[..len]).unwrap();
+        assert_eq!(small.num_glyphs, 3);
+        for c in ['A', 'é'] {
+            let original = font.glyph_for_char(c).unwrap();
+            assert_eq!(small.glyph_for_char(c), Some(original));
+        }
+    }
+
+    #[test]
+    fn stride_for_bundled_widths() {
+        // (font index, width, expected stride)
+        for (idx, width, stride) in [(0, 5, 1), (2, 8, 1), (3, 12, 2), (4, 16, 2), (5, 32, 4)] {
+            let (blob, _) = FONTS[idx];
+            let mut font = PSF2Font::new(blob).unwrap();
+            assert_eq!(font.bytes_per_row(), stride);
+
+            let glyph = font.glyph_for_utf8(b"W").unwrap();
+            assert_eq!(glyph.stride(), stride);
+            assert_eq!(glyph.dimensions(), (width, font.height as usize));
+            assert_eq!(stride * font.height as usize, font.bytes_per_glyph as usize);
+        }
+    }
+
+    // Which sizes resolve depends on the enabled features; run with e.g.
+    // `--features s8x16` to exercise the enabled path.
+    #[test]
+    fn font_bytes_by_size() {
+        #[cfg(feature = "s8x16")]
+        {
+            assert_eq!(font_bytes(Size::S8x16), Some(FONT_8X16));
+            assert_eq!(load(Size::S8x16).unwrap().height, 16);
+        }
+        #[cfg(not(feature = "s8x16"))]
+        {
+            assert!(font_bytes(Size::S8x16).is_none());
+            assert!(load(Size::S8x16).is_none());
+        }
+
+        #[cfg(feature = "all")]
+        for &(blob, size) in FONTS {
+            assert_eq!(font_bytes(size), Some(blob));
+        }
+        #[cfg(not(feature = "s32x64"))]
+        assert!(load(Size::S32x64).is_none());
+    }
+
+    #[test]
+    fn size_parse_and_display() {
+        for &(blob, size) in FONTS {
+            let font = PSF2Font::new(blob).unwrap();
+            assert_eq!(size.dimensions(), (font.width, font.height));
+
+            let name = format!("{}", size);
+            assert_eq!(name.parse::<Size>(), Ok(size));
+            assert_eq!(Size::try_from(size.dimensions()), Ok(size));
+        }
+
+        assert_eq!(format!("{}", Size::S8x16), "8x16");
+        assert_eq!(" 16x32\n".parse::<Size>(), Ok(Size::S16x32));
+        assert_eq!("\t5x8 ".parse::<Size>(), Ok(Size::S5x8));
+
+        for bad in ["", "8x", "x16", "8x15", "8 x 16", "8X16", "8x16x2", "-8x16"] {
+            assert_eq!(bad.parse::<Size>(), Err(PsfError::UnknownSize), "{:?}", bad);
+        }
+        assert_eq!(Size::try_from((7, 14)), Err(PsfError::UnknownSize));
+    }
+
+    #[test]
+    fn row_bytes_concatenate_to_glyph_data() {
+        for &(blob, _) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            let data = font.get_glyph_data(b"&").unwrap();
+            let glyph = Glyph::new(data, font.width as usize);
+
+            assert_eq!(glyph.row_bytes().count(), font.height as usize);
+            assert!(glyph.row_bytes().all(|row| row.len() == glyph.stride()));
+            assert_eq!(
+                glyph.row_bytes().flatten().copied().collect::<Vec<_>>(),
+                data
+            );
+
+            // The bool iterator agrees with the packed rows.
+            for (bytes, row) in glyph.row_bytes().zip(glyph) {
+                assert_eq!(GlyphRow::new(bytes, font.width as usize), row);
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_only_lookups() {
+        let (blob, _) = FONTS[1]; // 6x12
+        let mut font = PSF2Font::new(blob).unwrap();
+
+        assert!(font.glyph_for_utf8(b"A").is_some());
+        assert!(font.glyph_for_utf8(b"~").is_some());
+        let accented = font.get_glyph_data("é".as_bytes());
+        if cfg!(feature = "ascii-only") {
+            assert!(accented.is_none());
+        } else {
+            assert!(accented.is_some());
+        }
+    }
+
+    #[test]
+    fn fingerprint_is_stable_and_content_sensitive() {
+        let (blob, _) = FONTS[0];
+        let first = PSF2Font::new(blob).unwrap().fingerprint();
+        let mut font = PSF2Font::new(blob).unwrap();
+        font.glyph_for_utf8("é".as_bytes()); // cache state doesn't matter
+        assert_eq!(font.fingerprint(), first);
+
+        // Flipping one glyph bit changes the fingerprint.
+        let mut corrupted = blob.to_vec();
+        corrupted[32 + 8 * 65] ^= 0x80;
+        assert_ne!(PSF2Font::new(&corrupted).unwrap().fingerprint(), first);
+
+        // Every bundled font has its own fingerprint.
+        let mut prints: Vec<u64> = FONTS
+            .iter()
+            .map(|(blob, _)| PSF2Font::new(blob).unwrap().fingerprint())
+            .collect();
+        prints.sort_unstable();
+        prints.dedup();
+        assert_eq!(prints.len(), FONTS.len());
+    }
+
+    #[test]
+    fn row_random_access_matches_iteration() {
+        for &(blob, _) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            let glyph = font.glyph_for_utf8(b"R").unwrap();
+
+            for (y, row) in glyph.enumerate() {
+                assert_eq!(glyph.row(y), Some(row));
+            }
+            assert_eq!(glyph.row(font.height as usize), None);
+            assert_eq!(glyph.row(usize::MAX), None);
+            assert_eq!(glyph.len(), font.height as usize); // untouched
+        }
+    }
+
+    #[test]
+    fn glyphs_iterate_both_ways() {
+        let (blob, _) = FONTS[2]; // 8x16
+        let mut font = PSF2Font::new(blob).unwrap();
+        let text = "Hi, there!";
+
+        let forward: Vec<Option<Glyph>> = font.glyphs(text).collect();
+        let mut backward: Vec<Option<Glyph>> = font.glyphs(text).rev().collect();
+        backward.reverse();
+
+        assert_eq!(forward.len(), text.chars().count());
+        assert!(forward.iter().all(Option::is_some));
+        assert_eq!(forward, backward);
+
+        // Mixed: take one from each end.
+        let mut both = font.glyphs("ab");
+        let a = both.next().unwrap();
+        let b = both.next_back().unwrap();
+        assert!(both.next().is_none());
+        assert_eq!(a, font.glyph_for_char('a'));
+        assert_eq!(b, font.glyph_for_char('b'));
+    }
+
+    #[test]
+    fn cache_size_does_not_change_lookups() {
+        let (blob, _) = FONTS[0]; // 5x8, PSF2 with a unicode table
+        let text = "Hé, wörld! ░▒▓█ ←↑→↓ é░";
+        let mut cached = PSF2Font::new(blob).unwrap();
+        let mut uncached = PSF2Font::<0>::with_cache_size(blob).unwrap();
+        let mut tiny = PSF2Font::<2>::with_cache_size(blob).unwrap();
+
+        let expected: Vec<Option<Glyph>> = cached.glyphs(text).collect();
+        assert_eq!(uncached.glyphs(text).collect::<Vec<_>>(), expected);
+        assert_eq!(tiny.glyphs(text).collect::<Vec<_>>(), expected);
+        assert_eq!(DEFAULT_CACHE_SIZE, 64);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn sync_font_concurrent_lookups() {
+        let (blob, _) = FONTS[0]; // 5x8, PSF2 with a unicode table
+        let text = "Hé, wörld! ±÷° │┼ ☺";
+        let mut reference = PSF2Font::<0>::with_cache_size(blob).unwrap();
+        let expected: Vec<Option<Glyph>> = reference.glyphs(text).collect();
+
+        // A tiny cache keeps the threads evicting each other's entries.
+        let shared = SyncPSF2Font::<4>::with_cache_size(blob).unwrap();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..200 {
+                        let got: Vec<Option<Glyph>> =
+                            text.chars().map(|c| shared.glyph_for_char(c)).collect();
+                        assert_eq!(got, expected);
+                    }
+                });
+            }
+        });
+        assert_eq!(shared.font().width, 5);
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn primed_lookups_do_not_miss() {
+        let (blob, _) = FONTS[1]; // 6x12
+        let mut font = PSF2Font::new(blob).unwrap();
+        let label = "CPU ±2 42°C │ ß";
+
+        font.prime_cache(label);
+        assert_eq!(font.cache_stats(), CacheStats::default());
+
+        for c in label.chars() {
+            assert!(font.glyph_for_char(c).is_some());
+        }
+        let stats = font.cache_stats();
+        assert_eq!(stats.misses, 0);
+        assert_eq!(stats.hits, 4); // '±', '°', '│', 'ß'; the rest is ASCII
+
+        font.glyph_for_char('é');
+        assert_eq!(font.cache_stats().misses, 1);
+    }
+
+    #[test]
+    fn incomplete_or_malformed_utf8_is_rejected() {
+        let bad: [&[u8]; 9] = [
+            &[0xE2, 0x82],             // "€" cut after two bytes
+            &[0xF0, 0x9F, 0x98],       // four-byte scalar cut short
+            &[0x82],                   // lone continuation byte
+            &[0xC3, 0x41],             // continuation replaced by ASCII
+            &[0xE0, 0x80, 0xAF],       // overlong '/'
+            &[0xF0, 0x82, 0x82, 0xAC], // overlong '€'
+            &[0xED, 0xA0, 0x80],       // UTF-16 surrogate
+            &[0xF4, 0x90, 0x80, 0x80], // past U+10FFFF
+            &[0xC3, 0xA9, 0xC3],       // 'é' followed by a truncated scalar
+        ];
+
+        let (blob, _) = FONTS[0]; // unicode table
+        let mut table_font = PSF2Font::new(blob).unwrap();
+        let direct = synthetic_font(32, 0, 256, 8, 8, &[]);
+        let mut direct_font = PSF2Font::new(&direct).unwrap();
+
+        for bytes in bad {
+            assert_eq!(table_font.get_glyph_data(bytes), None, "{bytes:02X?}");
+            assert_eq!(direct_font.get_glyph_data(bytes), None, "{bytes:02X?}");
+        }
+        #[cfg(not(feature = "ascii-only"))]
+        {
+            assert!(table_font.get_glyph_data("é".as_bytes()).is_some());
+            assert!(direct_font.get_glyph_data("é".as_bytes()).is_some());
+        }
+    }
+
+    #[test]
+    fn rasterize_line_matches_per_glyph_blits() {
+        let (blob, _) = FONTS[2]; // 8x16
+        let mut font = PSF2Font::new(blob).unwrap();
+        let stride = 24; // one spare cell of padding per row
+        let mut out = [0xAAu8; 3 * 16];
+
+        assert_eq!(font.rasterize_line("Hi", &mut out, stride), Ok((16, 16)));
+        for (cell, c) in "Hi".chars().enumerate() {
+            let glyph = font.glyph_for_char(c).unwrap();
+            for (y, row) in glyph.enumerate() {
+                for (x, on) in row.enumerate() {
+                    let bit = y * stride + cell * 8 + x;
+                    assert_eq!(
+                        out[bit / 8] & (0x80 >> (bit % 8)) != 0,
+                        on,
+                        "{c} ({x}, {y})"
+                    );
+                }
+            }
+        }
+        // The padding past the line is left alone.
+        assert!(out.chunks(3).all(|row| row[2] == 0xAA));
+
+        // Too narrow a stride, or too few rows.
+        assert_eq!(
+            font.rasterize_line("Hi!!", &mut out, stride),
+            Err(PsfError::BufferTooSmall)
+        );
+        assert_eq!(
+            font.rasterize_line("Hi", &mut out[..3 * 15], stride),
+            Err(PsfError::BufferTooSmall)
+        );
+        assert_eq!(
+            font.rasterize_line("Hi", &mut out, usize::MAX),
+            Err(PsfError::BufferTooSmall)
+        );
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn unicode_table_iter_reads_every_record() {
+        let (blob, _) = FONTS[0]; // 5x8
+        let font = PSF2Font::new(blob).unwrap();
+        let table = font.unicode_table();
+        assert_eq!(table.as_ptr(), blob[blob.len() - table.len()..].as_ptr());
+
+        let mut iter = UnicodeTableIter::new(table, font.num_glyphs);
+        let mut last = 0;
+        for (glyph, entry) in iter.by_ref() {
+            assert!(glyph >= last && glyph < font.num_glyphs);
+            assert!(core::str::from_utf8(entry).is_ok());
+            last = glyph;
+        }
+        assert_eq!(iter.records_read(), font.num_glyphs);
+
+        // Glyphs past the first empty record (`FF FF`) are still found.
+        let (late, entry) = font.unicode_entries().last().unwrap();
+        let mut font = PSF2Font::new(blob).unwrap();
+        assert_eq!(font.get_glyph_data(entry), font.glyph_by_idx(late));
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn coverage_counts_codepoints_not_glyphs() {
+        let (blob, _) = FONTS[2]; // 8x16, a PSF1 font with a UCS-2 table
+        let mut font = PSF2Font::new(blob).unwrap();
+        let covered = font.coverage();
+        assert!(covered > font.num_glyphs as usize, "{covered}");
+        assert!(covered < 0x10000);
+
+        // The UCS-2 table now backs lookups as well.
+        let arrow = font.glyph_for_utf8("↑".as_bytes()).unwrap();
+        assert_eq!(font.glyph_for_char('▲'), Some(arrow)); // same glyph
+        assert_eq!(font.get_glyph_data("▲".as_bytes()), font.glyph_by_idx(24));
+        assert!(font.glyph_for_char('\u{10348}').is_none());
+
+        // PSF2: every single-codepoint entry, sequences excluded.
+        let (blob, _) = FONTS[0];
+        let font = PSF2Font::new(blob).unwrap();
+        assert!(font.coverage() > 0 && font.coverage() <= font.unicode_entries().count());
+
+        let direct = synthetic_font(32, 0, 128, 8, 8, &[]);
+        assert_eq!(PSF2Font::new(&direct).unwrap().coverage(), 128);
+    }
+
+    #[test]
+    fn layout_tabs_and_control_policy() {
+        let (blob, _) = FONTS[1]; // 6x12
+        let mut font = PSF2Font::new(blob).unwrap();
+        let tab4 = LayoutOptions {
+            tab_width: 4,
+            ..LayoutOptions::default()
+        };
+
+        // 'a' in cell 0, the tab runs to cell 4, 'b' in cell 4.
+        assert_eq!(font.measure_str("a\tb", &tab4), (5 * 6, 12));
+        let mut xs = Vec::new();
+        assert_eq!(
+            font.render_str("a\tb", &tab4, |x, _| xs.push(x)),
+            (5 * 6, 12)
+        );
+        assert!(xs.iter().all(|&x| x < 6 || (24..30).contains(&x)));
+        assert!(xs.iter().any(|&x| x >= 24));
+        // A tab already on a stop still advances to the next one.
+        assert_eq!(font.measure_str("abcd\tb", &tab4).0, 9 * 6);
+        assert_eq!(font.measure_str("ab\ncdefg\n", &tab4), (5 * 6, 3 * 12));
+
+        let with = |control| LayoutOptions { control, ..tab4 };
+        assert_eq!(
+            font.measure_str("a\x07b", &with(ControlPolicy::Skip)).0,
+            2 * 6
+        );
+        assert_eq!(
+            font.measure_str("a\x07b", &with(ControlPolicy::AdvanceCell))
+                .0,
+            3 * 6
+        );
+
+        let lit = |font: &mut PSF2Font, control| {
+            let mut n = 0;
+            font.render_str("\x07", &with(control), |_, _| n += 1);
+            n
+        };
+        assert_eq!(lit(&mut font, ControlPolicy::AdvanceCell), 0);
+        assert!(lit(&mut font, ControlPolicy::Replacement) > 0);
+    }
+
+    #[test]
+    fn xor_marks_differing_pixels() {
+        let (blob, _) = FONTS[3]; // 12x24, two-byte rows
+        let mut font = PSF2Font::new(blob).unwrap();
+        let a = font.glyph_for_char('A').unwrap();
+        let b = font.glyph_for_char('B').unwrap();
+
+        assert!(a.xor(&a).unwrap().all(|mut row| row.all(|on| !on)));
+
+        // XOR against the inverse sets every visible pixel.
+        let inverse: Vec<GlyphRow> = a.inverted().collect();
+        for (row, inv) in a.zip(inverse) {
+            let pixels: Vec<bool> = row.zip(inv).map(|(x, y)| x != y).collect();
+            assert_eq!(pixels, [true; 12]);
+        }
+
+        // Toggling the differing pixels of A turns it into B.
+        for ((ra, rb), rx) in a.zip(b).zip(a.xor(&b).unwrap()) {
+            let toggled: Vec<bool> = ra.zip(rx).map(|(p, t)| p != t).collect();
+            assert_eq!(toggled, rb.collect::<Vec<bool>>());
+        }
+
+        let (small, _) = FONTS[0];
+        let mut small = PSF2Font::new(small).unwrap();
+        assert!(a.xor(&small.glyph_for_char('A').unwrap()).is_none());
+    }
+
+    #[test]
+    fn row_runs() {
+        let runs = |bytes: &[u8], width| GlyphRow::new(bytes, width).runs().collect::<Vec<_>>();
+
+        assert_eq!(runs(&[0b11100011], 8), [(true, 3), (false, 3), (true, 2)]);
+        assert_eq!(runs(&[0xFF, 0xF0], 12), [(true, 12)]);
+        assert_eq!(runs(&[0x00, 0x00], 12), [(false, 12)]);
+        assert_eq!(
+            runs(&[0b10101111], 5),
+            [(true, 1), (false, 1), (true, 1), (false, 1), (true, 1)]
+        );
+        assert_eq!(runs(&[], 0), []);
+
+        // Runs start from wherever the row iterator is.
+        let mut row = GlyphRow::new(&[0b11100011], 8);
+        row.nth(3);
+        assert_eq!(row.runs().collect::<Vec<_>>(), [(false, 2), (true, 2)]);
+    }
+
+    #[test]
+    fn nth_agrees_with_repeated_next() {
+        fn walk<I: Iterator>(iter: &mut I, n: usize) -> Option<I::Item> {
+            for _ in 0..n {
+                iter.next()?;
+            }
+            iter.next()
+        }
+
+        let (blob, _) = FONTS[5]; // 32x64
+        let mut font = PSF2Font::new(blob).unwrap();
+        let glyph = font.glyph_for_char('@').unwrap();
+
+        for n in [0, 1, 17, 63, 64, 1000, usize::MAX] {
+            let (mut fast, mut slow) = (glyph, glyph);
+            let jumped = fast.nth(n);
+            let walked = walk(&mut slow, n);
+            assert_eq!(jumped, walked, "row {n}");
+            assert_eq!(fast.len(), slow.len());
+
+            let row = glyph.row(40).unwrap();
+            let (mut fast, mut slow) = (row, row);
+            let jumped = fast.nth(n);
+            let walked = walk(&mut slow, n);
+            assert_eq!(jumped, walked, "column {n}");
+            assert_eq!(fast.len(), slow.len());
+        }
+    }
+
+    #[test]
+    fn set_pixel_count_ignores_padding() {
+        for (blob, _) in [FONTS[0], FONTS[3]] {
+            // 5x8 and 12x24 both pad their rows.
+            let mut font = PSF2Font::new(blob).unwrap();
+            for c in ['A', '@', '#', ' '] {
+                let glyph = font.glyph_for_char(c).unwrap();
+                let counted = glyph.flatten().filter(|&on| on).count();
+                assert_eq!(glyph.set_pixel_count(), counted, "{c} at {}px", font.width);
+            }
+        }
+
+        // Padding bits set on purpose: 3 wide, rows of 0b111_11111.
+        let glyph = Glyph::new(&[0xFF; 4], 3);
+        assert_eq!(glyph.set_pixel_count(), 3 * 4);
+        assert_eq!(Glyph::new(&[0xFF; 4], 8).set_pixel_count(), 32);
+        assert_eq!(Glyph::new(&[], 0).set_pixel_count(), 0);
+    }
+
+    // Wider than anything Spleen ships, and wider than a `u32` row.
+    #[test]
+    fn forty_pixel_wide_font() {
+        let blob = synthetic_font(32, 0, 256, 40, 8, &[]);
+        let font = PSF2Font::new(&blob).unwrap();
+        assert_eq!((font.bytes_per_glyph, font.bytes_per_row()), (40, 5));
+
+        let glyph = Glyph::new(font.glyph_by_idx(0xA5).unwrap(), 40); // 0b10100101
+        let pattern: Vec<bool> = (0..40).map(|x| 0xA5u8 & (0x80 >> (x % 8)) != 0).collect();
+        assert_eq!(glyph.len(), 8);
+        for row in glyph {
+            assert_eq!(row.len(), 40);
+            assert_eq!(row.collect::<Vec<bool>>(), pattern);
+        }
+        assert!(glyph.row_bytes().all(|row| row == [0xA5; 5]));
+        assert_eq!(glyph.set_pixel_count(), 4 * 5 * 8);
+        assert!(glyph
+            .inverted()
+            .zip(glyph)
+            .all(|(inv, row)| inv.zip(row).all(|(a, b)| a != b)));
+
+        // 37 wide: the same 5-byte rows, with the last 3 bits as padding.
+        let blob = synthetic_font(32, 0, 256, 37, 8, &[]);
+        let font = PSF2Font::new(&blob).unwrap();
+        let full = Glyph::new(font.glyph_by_idx(0xFF).unwrap(), 37); // all bits set
+        assert!(full
+            .clone()
+            .all(|row| row.len() == 37 && row.clone().all(|on| on)));
+        assert_eq!(full.set_pixel_count(), 37 * 8);
+    }
+
+    // A byte pattern whose value differs under either byte order, so a host-order read
+    // anywhere in the parser would show up here on little- and big-endian targets alike.
+    #[test]
+    fn header_fields_are_little_endian_on_any_host() {
+        let mut bytes = [0u8; 32];
+        bytes[..4].copy_from_slice(&PSF2_MAGIC);
+        bytes[8..12].copy_from_slice(&[32, 0, 0, 0]);
+        bytes[16..20].copy_from_slice(&[0x04, 0x03, 0x02, 0x01]);
+        bytes[24..28].copy_from_slice(&[0x10, 0x00, 0x00, 0x00]);
+        let header = PSF2Header::from_bytes(&bytes).unwrap();
+        assert_eq!(header.header_size, 32);
+        assert_eq!(header.num_glyphs, 0x0102_0304);
+        assert_eq!(header.height, 16);
+
+        // The same header written big-endian (as a buggy tool on a BE host might) is rejected.
+        let mut swapped = bytes;
+        for field in swapped.chunks_exact_mut(4) {
+            field.reverse();
+        }
+        assert_eq!(
+            PSF2Header::from_bytes(&swapped).err(),
+            Some(PsfError::InvalidMagic)
+        );
+
+        // Bundled fonts report the metrics the spec's little-endian fields encode.
+        let (blob, size) = FONTS[4]; // 16x32
+        let font = PSF2Font::new(blob).unwrap();
+        assert_eq!((font.width, font.height), size.dimensions());
+        assert_eq!(
+            font.num_glyphs,
+            u32::from_le_bytes(blob[16..20].try_into().unwrap())
+        );
+    }
+
+    #[test]
+    fn oob_policy_for_over_range_indices() {
+        // No table, so 'Ā' (U+0100) maps straight to index 256 of 256 glyphs.
+        let blob = synthetic_font(32, 0, 256, 8, 8, &[]);
+        let mut font = PSF2Font::new(&blob).unwrap();
+        assert_eq!(font.oob_policy(), OobPolicy::None);
+        assert_eq!(font.glyph_by_idx(256), None);
+        assert_eq!(font.get_glyph_data("Ā".as_bytes()), None);
+
+        font.set_oob_policy(OobPolicy::Clamp);
+        assert_eq!(font.glyph_by_idx(256), Some(&[255u8; 8][..]));
+        assert_eq!(font.glyph_by_idx(u32::MAX), font.glyph_by_idx(255));
+        #[cfg(not(feature = "ascii-only"))] // which never maps non-ASCII to an index
+        assert_eq!(font.get_glyph_data("Ā".as_bytes()), font.glyph_by_idx(255));
+
+        // U+FFFD is out of range too, so the replacement falls back to '?'.
+        font.set_oob_policy(OobPolicy::Replacement);
+        assert_eq!(font.glyph_by_idx(300), Some(&[b'?'; 8][..]));
+        assert_eq!(font.glyph_by_idx(0), Some(&[0u8; 8][..])); // in range: untouched
+
+        font.set_oob_policy(OobPolicy::None);
+        assert_eq!(font.glyph_by_idx(256), None);
+
+        // Neither replacement glyph present: behaves like `None`.
+        let tiny = synthetic_font(32, 0, 16, 8, 8, &[]);
+        let mut tiny = PSF2Font::new(&tiny).unwrap();
+        tiny.set_oob_policy(OobPolicy::Replacement);
+        assert_eq!(tiny.glyph_by_idx(16), None);
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn oob_replacement_uses_the_fonts_replacement_glyph() {
+        let (blob, _) = FONTS[2]; // 8x16 maps U+FFFD
+        let mut font = PSF2Font::new(blob).unwrap();
+        font.set_oob_policy(OobPolicy::Replacement);
+        let replacement = font.get_glyph_data("\u{FFFD}".as_bytes());
+        assert!(replacement.is_some());
+        assert_eq!(font.glyph_by_idx(font.num_glyphs), replacement);
+    }
+
+    #[test]
+    fn glyph_width_and_height() {
+        for &(blob, size) in FONTS {
+            let font = PSF2Font::new(blob).unwrap();
+            let mut glyph = Glyph::new(font.glyph_by_idx(0).unwrap(), font.width as usize);
+            let (w, h) = size.dimensions();
+            assert_eq!((glyph.width(), glyph.height()), (w as usize, h as usize));
+            glyph.next();
+            assert_eq!(glyph.height(), h as usize - 1);
+        }
+        assert_eq!(Glyph::new(&[], 0).dimensions(), (0, 0));
+    }
+
+    // Lookups hand out the one `glyph::Glyph` type, borrowing the font's bitmap rows.
+    #[test]
+    fn looked_up_glyph_iterates_font_rows() {
+        let (blob, _) = FONTS[1]; // 6x12
+        let mut font = PSF2Font::new(blob).unwrap();
+        let glyph: glyph::Glyph = font.glyph_for_utf8(b"W").unwrap();
+        let data = font.glyph_by_idx(b'W' as u32).unwrap();
+
+        assert_eq!(glyph.len(), 12);
+        for (row, &byte) in glyph.zip(data) {
+            let expected: Vec<bool> = (0..6).map(|x| byte & (0x80 >> x) != 0).collect();
+            assert_eq!(row.collect::<Vec<bool>>(), expected);
+        }
+    }
+
+    #[test]
+    fn line_gap_spaces_out_lines() {
+        let (blob, _) = FONTS[1]; // 6x12
+        let mut font = PSF2Font::new(blob).unwrap();
+        let options = LayoutOptions::default();
+        assert_eq!(font.line_height(), 12);
+
+        font.set_line_gap(2);
+        assert_eq!(font.line_height(), 14);
+        assert_eq!(font.measure_str("one", &options), (18, 12));
+        assert_eq!(font.measure_str("one\ntwo", &options), (18, 12 + 2 + 12));
+
+        // The second line starts a full line height further down.
+        let mut ys = Vec::new();
+        let size = font.render_str("_\n_", &options, |_, y| ys.push(y));
+        assert_eq!(size, (6, 26));
+        let (first, second) = ys.split_at(ys.len() / 2);
+        assert!(first.iter().zip(second).all(|(a, b)| b - a == 14));
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn font_chain_falls_back_in_order() {
+        let primary = PSF2Font::new(FONTS[1].0).unwrap(); // 6x12, no arrows
+        let fallback = PSF2Font::new(FONTS[2].0).unwrap(); // 8x16
+        let mut chain = FontChain::new([primary, fallback]);
+
+        let (i, a) = chain.glyph_for_char('A').unwrap();
+        assert_eq!((i, a.width()), (0, 6));
+
+        let (i, arrow) = chain.glyph_for_char('↑').unwrap();
+        assert_eq!((i, arrow.dimensions()), (1, (8, 16)));
+        assert_eq!(chain.font(i).unwrap().height, 16);
+
+        assert!(chain.glyph_for_char('\u{10348}').is_none());
+        assert!(chain.font(2).is_none());
+    }
+
+    #[test]
+    fn header_validate_checks_each_invariant() {
+        let good = PSF2Header::new(PSF2_MAGIC, 0, 32, 0, 256, 24, 12, 12);
+        assert_eq!(good.validate(), Ok(()));
+        for &(blob, _) in FONTS.iter().filter(|(blob, _)| blob[0] == PSF2_MAGIC[0]) {
+            assert_eq!(PSF2Header::from_bytes(blob).unwrap().validate(), Ok(()));
+        }
+
+        let broken = [
+            (
+                PSF2Header {
+                    magic: [0x36, 0x04, 0, 0],
+                    ..good
+                },
+                PsfError::InvalidMagic,
+            ),
+            (
+                PSF2Header { version: 1, ..good },
+                PsfError::UnsupportedVersion,
+            ),
+            (
+                PSF2Header {
+                    header_size: 31,
+                    ..good
+                },
+                PsfError::InvalidHeaderSize,
+            ),
+            (PSF2Header { width: 0, ..good }, PsfError::InvalidDimensions),
+            (
+                PSF2Header { height: 0, ..good },
+                PsfError::InvalidDimensions,
+            ),
+            (
+                PSF2Header {
+                    num_glyphs: 0,
+                    ..good
+                },
+                PsfError::InvalidDimensions,
+            ),
+            (
+                PSF2Header {
+                    bytes_per_glyph: 12,
+                    ..good
+                },
+                PsfError::GlyphSizeMismatch,
+            ),
+            (
+                PSF2Header { width: 17, ..good },
+                PsfError::GlyphSizeMismatch,
+            ),
+        ];
+        for (header, err) in broken {
+            assert_eq!(header.validate(), Err(err), "{header:?}");
+        }
+    }
+
+    #[test]
+    fn display_summarizes_font() {
+        let font = PSF2Font::new(FONTS[2].0).unwrap(); // 8x16
+        let summary = format!("{font}");
+        assert!(summary.contains("8x16"), "{summary}");
+        assert!(summary.starts_with("PSF1 8x16, 512 glyphs, "), "{summary}");
+
+        let table = if cfg!(feature = "ascii-only") {
+            "no unicode table"
+        } else {
+            "unicode table present"
+        };
+        let font = PSF2Font::new(FONTS[3].0).unwrap();
+        assert_eq!(
+            format!("{font}"),
+            format!("PSF2 12x24, {} glyphs, {table}", font.num_glyphs)
+        );
+
+        let blob = synthetic_font(32, 0, 16, 8, 8, &[]);
+        let font = PSF2Font::new(&blob).unwrap();
+        assert_eq!(format!("{font}"), "PSF2 8x8, 16 glyphs, no unicode table");
+    }
+
+    #[test]
+    fn fit_pads_and_crops_around_the_center() {
+        let small = PSF2Font::new(FONTS[0].0).unwrap(); // 5x8
+        let glyph = Glyph::new(small.glyph_by_idx(b'#' as u32).unwrap(), 5);
+        let src = pixels(glyph);
+
+        // Padding 5x8 to 8x16: 1 column left, 2 right (odd), 4 rows above and below.
+        let fitted = pixels(glyph.fit(8, 16));
+        assert_eq!((fitted.len(), fitted[0].len()), (16, 8));
+        for (y, row) in fitted.iter().enumerate() {
+            for (x, &on) in row.iter().enumerate() {
+                let inside = (1..6).contains(&x) && (4..12).contains(&y);
+                assert_eq!(on, inside && src[y - 4][x - 1], "({x}, {y})");
+            }
+        }
+
+        // Cropping 12x24 to 8x16: 2 columns and 4 rows off each side.
+        let large = PSF2Font::new(FONTS[3].0).unwrap();
+        let glyph = Glyph::new(large.glyph_by_idx(b'@' as u32).unwrap(), 12);
+        let src = pixels(glyph);
+        let fitted = pixels(glyph.fit(8, 16));
+        for (y, row) in fitted.iter().enumerate() {
+            assert_eq!(row[..], src[y + 4][2..10]);
+        }
+
+        // Odd crop: 12 wide into 9 keeps columns 1..10, dropping the extra one on the right.
+        let fitted = pixels(glyph.fit(9, 24));
+        assert!(fitted.iter().zip(&src).all(|(f, s)| f[..] == s[1..10]));
+        assert_eq!(glyph.fit(0, 0).count(), 0);
+
+        // A huge cell pads the glyph far out of view instead of wrapping around.
+        let cap = crate::glyph::MAX_ROW_BYTES * 8;
+        let fitted = pixels(glyph.fit(usize::MAX, usize::MAX).take(24));
+        assert_eq!(fitted.len(), 24);
+        assert!(fitted.iter().all(|row| row.len() == cap));
+        assert!(fitted.iter().flatten().all(|&on| !on));
+    }
+
+    #[test]
+    fn zero_width_marks_take_no_advance() {
+        let (blob, _) = FONTS[1]; // 6x12
+        let mut font = PSF2Font::new(blob).unwrap();
+        let options = LayoutOptions::default();
+
+        assert_eq!(font.advance_of('e'), 6);
+        assert_eq!(font.advance_of('\u{301}'), 0); // combining acute, by default
+        assert_eq!(font.measure_str("e\u{301}x", &options).0, 2 * 6);
+        // Leading a line, a mark keeps its own cell.
+        assert_eq!(font.measure_str("\u{301}x", &options).0, 2 * 6);
+
+        // Treat the spacing acute as combining and it's drawn over the 'e'.
+        const ACUTE: &[core::ops::RangeInclusive<char>] = &['´'..='´'];
+        font.set_zero_width(ACUTE);
+        assert_eq!(font.advance_of('´'), 0);
+        assert_eq!(font.advance_of('\u{301}'), 6);
+        let mut lit = Vec::new();
+        assert_eq!(
+            font.render_str("e´", &options, |x, y| lit.push((x, y))).0,
+            6
+        );
+        assert!(lit.iter().all(|&(x, _)| x < 6));
+
+        if cfg!(not(feature = "ascii-only")) {
+            let mut expected = Vec::new();
+            font.render_str("e", &options, |x, y| expected.push((x, y)));
+            font.render_str("´", &options, |x, y| expected.push((x, y)));
+            expected.sort();
+            expected.dedup();
+            lit.sort();
+            lit.dedup(); // pixels both glyphs set are plotted twice
+            assert_eq!(lit, expected);
+        }
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn sorted_index_matches_linear_scan() {
+        for i in [0, 2, 3] {
+            // 5x8, 8x16 (PSF1) and 12x24
+            let (blob, _) = FONTS[i];
+            let mut scanned = PSF2Font::<0>::with_cache_size(blob).unwrap();
+            let mut indexed = PSF2Font::<0>::with_cache_size(blob).unwrap();
+
+            let needed = indexed.build_index(&mut []);
+            assert!(needed >= indexed.coverage());
+            let mut short = std::vec![(0, 0); needed - 1];
+            assert_eq!(indexed.build_index(&mut short), needed);
+            let mut scratch = std::vec![(0, 0); needed];
+            assert_eq!(indexed.build_index(&mut scratch), needed);
+
+            for cp in (0x80..0x3000).chain(0xE000..0x10000).chain([0x1F600]) {
+                let Some(c) = char::from_u32(cp) else {
+                    continue;
+                };
+                assert_eq!(
+                    indexed.glyph_for_char(c),
+                    scanned.glyph_for_char(c),
+                    "{c:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn utf8_seq_len_classifies_leading_bytes() {
+        use crate::utf8::utf8_seq_len;
+
+        for c in ['A', 'é', '▒', '😀'] {
+            let mut buf = [0u8; 4];
+            let bytes = c.encode_utf8(&mut buf).as_bytes();
+            assert_eq!(utf8_seq_len(bytes[0]), Some(bytes.len()), "{c:?}");
+        }
+        // Continuation bytes, overlong-only leads and leads past U+10FFFF.
+        for b in [0x80, 0xBF, 0xC0, 0xC1, 0xF5, 0xFF] {
+            assert_eq!(utf8_seq_len(b), None, "{b:#x}");
+        }
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn cache_hits_skip_reindexing() {
+        use crate::psf::INDEX_COMPUTATIONS;
+
+        fn computations<'a, const C: usize>(font: &mut PSF2Font<'a, C>) -> (Option<&'a [u8]>, u32) {
+            INDEX_COMPUTATIONS.with(|n| n.set(0));
+            let glyph = font.get_glyph_data("é".as_bytes());
+            (glyph, INDEX_COMPUTATIONS.with(|n| n.get()))
+        }
+
+        let (blob, _) = FONTS[2];
+        let mut font = PSF2Font::new(blob).unwrap();
+        let mut uncached = PSF2Font::<0>::with_cache_size(blob).unwrap();
+
+        let (first, n) = computations(&mut font);
+        assert_eq!(n, 1);
+        for _ in 0..10 {
+            assert_eq!(computations(&mut font), (first, 0));
+            assert_eq!(computations(&mut uncached), (first, 1));
+        }
+    }
+
+    #[cfg(feature = "raster")]
+    #[test]
+    fn downscale_2x_gray_averages_blocks() {
+        // 5x3: the odd last column and row are dropped, giving 2x1 output.
+        // Block 0 has three of its four pixels set, block 1 just one.
+        let data = [0b1100_0000, 0b1001_1000, 0b1111_1000];
+        let glyph = Glyph::new(&data, 5);
+
+        let mut out = [7u8; 3 * 2];
+        glyph.downscale_2x_gray(&mut out, 3, 1, 1);
+        assert_eq!(out, [7, 7, 7, 7, 192, 64]);
+
+        // Blocks past the stride or the end of the buffer are skipped.
+        let mut out = [7u8; 2];
+        glyph.downscale_2x_gray(&mut out, 2, 1, 0);
+        assert_eq!(out, [7, 192]);
+        let levels: Vec<u8> = [
+            0b0000_0000,
+            0b1000_0000,
+            0b1100_0000,
+            0b1110_0000,
+            0b1111_0000,
+        ]
+        .iter()
+        .map(|&top| {
+            let data = [top & 0b1100_0000, (top << 2) & 0b1100_0000];
+            let mut px = [0u8];
+            Glyph::new(&data, 2).downscale_2x_gray(&mut px, 1, 0, 0);
+            px[0]
+        })
+        .collect();
+        assert_eq!(levels, [0, 64, 128, 192, 255]);
+    }
+
+    #[test]
+    fn outline_of_a_filled_rectangle() {
+        // A 4x3 block in a 6x5 glyph.
+        let data = [0x00, 0b0111_1000, 0b0111_1000, 0b0111_1000, 0x00];
+        let glyph = Glyph::new(&data, 6);
+
+        assert_eq!(
+            render(glyph.inner_outline()),
+            ["......", ".####.", ".#..#.", ".####.", "......"]
+        );
+        assert_eq!(
+            render(glyph.outline()),
+            [".####.", "#....#", "#....#", "#....#", ".####."]
+        );
+
+        // A block touching the border keeps its inner edge there, but its halo is cut off.
+        let full = [0b1111_0000; 3];
+        let glyph = Glyph::new(&full, 4);
+        assert_eq!(render(glyph.inner_outline()), ["####", "#..#", "####"]);
+        assert_eq!(render(glyph.outline()), ["....", "....", "...."]);
+    }
+
+    #[test]
+    fn with_shadow_widens_the_cell() {
+        // An L in a 3x3 glyph.
+        let data = [0b1000_0000, 0b1000_0000, 0b1110_0000];
+        let glyph = Glyph::new(&data, 3);
+
+        assert_eq!(
+            render(glyph.with_shadow(1, 1)),
+            ["#...", "##..", "###.", ".###"]
+        );
+        assert_eq!(
+            render(glyph.with_shadow(-2, 0)),
+            ["#.#..", "#.#..", "#####"]
+        );
+        assert_eq!(render(glyph.with_shadow(0, 0)), render(glyph));
+
+        // Extreme offsets don't overflow. One copy stays at the origin while the other
+        // is pushed past the capped width and the first rows.
+        let cap = crate::glyph::MAX_ROW_BYTES * 8;
+        for (dx, dy) in [(isize::MIN, isize::MIN), (isize::MAX, isize::MAX)] {
+            let rows = render(glyph.with_shadow(dx, dy).take(4));
+            let lefts: Vec<_> = rows.iter().map(|row| &row[..4]).collect();
+            assert_eq!(lefts, ["#...", "#...", "###.", "...."], "({dx}, {dy})");
+            assert!(rows.iter().all(|row| row.len() == cap));
+        }
+    }
+
+    #[test]
+    fn iterating_a_glyph_reference_leaves_it_untouched() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        let glyph = font.glyph_for_utf8(b"A").unwrap();
+
+        let first: Vec<GlyphRow> = (&glyph).into_iter().collect();
+        let mut second = Vec::new();
+        for row in &glyph {
+            second.push(row);
+        }
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 16);
+        assert_eq!(glyph.len(), 16);
+    }
+
+    #[test]
+    fn size_hints_are_exact() {
+        let data = [0xA5; 2 * 5];
+        let mut glyph = Glyph::new(&data, 12);
+        for len in (0..=5).rev() {
+            assert_eq!(glyph.size_hint(), (len, Some(len)));
+            assert_eq!(glyph.len(), len);
+            let Some(mut row) = glyph.next() else { break };
+            for len in (0..=12).rev() {
+                assert_eq!(row.size_hint(), (len, Some(len)));
+                assert_eq!(row.len(), len);
+                row.next();
+            }
+        }
+        assert_eq!(glyph.next_back(), None);
+
+        let mut glyph = Glyph::new(&data, 12);
+        glyph.nth(1);
+        glyph.next_back();
+        assert_eq!(glyph.size_hint(), (2, Some(2)));
+        assert_eq!(Glyph::new(&data, 0).size_hint(), (0, Some(0)));
+        assert_eq!(Glyph::new(&data, 0).next(), None);
+        assert_eq!(Glyph::new(&data, 0).next_back(), None);
+    }
+
+    #[test]
+    fn lsb_first_reverses_columns_within_bytes() {
+        let data = [0b1100_0001, 0b1010_0000];
+        let msb: Vec<bool> = GlyphRow::new(&data, 12).collect();
+        let lsb: Vec<bool> = GlyphRow::with_bit_order(&data, 12, BitOrder::LsbFirst).collect();
+        let bits = |s: &str| s.chars().map(|c| c == '1').collect::<Vec<_>>();
+        assert_eq!(msb, bits("110000011010"));
+        assert_eq!(lsb, bits("100000110000"));
+
+        let glyph = Glyph::new(&data, 12);
+        assert_eq!(glyph.bit_order(BitOrder::MsbFirst), glyph);
+        let reversed = glyph.bit_order(BitOrder::LsbFirst);
+        assert_eq!(reversed.row(0).unwrap().collect::<Vec<_>>(), lsb[..12]);
+        assert_eq!(reversed.clone().next(), reversed.row(0));
+        // Padding is the high nibble of the last byte now, so 0b1010 there isn't counted.
+        assert_eq!(glyph.set_pixel_count(), 5);
+        assert_eq!(reversed.set_pixel_count(), 3);
+    }
+
+    #[test]
+    fn static_fonts_can_be_shared_between_threads() {
+        use std::sync::{Mutex, OnceLock};
+
+        static FONT: PSF2Font<'static> =
+            match PSF2Font::from_static(include_bytes!("../fonts/spleen-8x16.psfu")) {
+                Ok(font) => font,
+                Err(_) => panic!("bad font"),
+            };
+        static SHARED: OnceLock<Mutex<PSF2Font<'static>>> = OnceLock::new();
+
+        let expected = PSF2Font::new(FONTS[2].0).unwrap().get_glyph_data(b"A");
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    assert_eq!(FONT.width, 8);
+                    let shared =
+                        SHARED.get_or_init(|| Mutex::new(PSF2Font::new(FONTS[2].0).unwrap()));
+                    shared.lock().unwrap().get_glyph_data(b"A")
+                })
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn non_blank_glyph_count_skips_empty_cells() {
+        for &(blob, _) in FONTS {
+            let font = PSF2Font::new(blob).unwrap();
+            let count = font.non_blank_glyph_count();
+            assert!(count > 0 && count < font.num_glyphs, "{font}: {count}");
+        }
+
+        // Glyph i is filled with byte i, so only glyph 0 is blank.
+        let blob = synthetic_font(32, 0, 4, 8, 2, &[]);
+        assert_eq!(PSF2Font::new(&blob).unwrap().non_blank_glyph_count(), 3);
+    }
+
+    #[test]
+    fn rows_range_matches_full_iteration() {
+        for &(blob, _) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            let glyph = font.glyph_for_utf8(b"g").unwrap();
+            let rows: Vec<GlyphRow> = glyph.collect();
+            let h = rows.len();
+
+            for (start, end) in [
+                (0, h),
+                (1, 3),
+                (h / 2, h),
+                (h - 1, h + 5),
+                (3, 1),
+                (h, h + 1),
+            ] {
+                let band: Vec<GlyphRow> = glyph.rows_range(start, end).collect();
+                let expected = rows.get(start..end.min(h)).unwrap_or(&[]);
+                assert_eq!(band, expected, "rows {start}..{end}");
+            }
+        }
+    }
+
+    #[test]
+    fn zero_sized_glyphs_are_rejected() {
+        // An all-zero header after the magic.
+        let mut blob = PSF2_MAGIC.to_vec();
+        blob.resize(64, 0);
+        assert_eq!(
+            PSF2Font::new(&blob).err(),
+            Some(PsfError::InvalidHeaderSize)
+        );
+
+        for (width, height) in [(0, 16), (8, 0)] {
+            let blob = synthetic_font(32, 0, 4, width, height, &[]);
+            assert_eq!(
+                PSF2Font::new(&blob).err(),
+                Some(PsfError::InvalidDimensions)
+            );
+        }
+
+        // bytes_per_glyph is the u32 at offset 20.
+        let mut blob = synthetic_font(32, 0, 4, 8, 16, &[]);
+        blob[20..24].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(
+            PSF2Font::new(&blob).err(),
+            Some(PsfError::InvalidDimensions)
+        );
+        blob[20..24].copy_from_slice(&15u32.to_le_bytes());
+        assert_eq!(
+            PSF2Font::new(&blob).err(),
+            Some(PsfError::GlyphSizeMismatch)
+        );
+        // Padding after each glyph is allowed.
+        blob[20..24].copy_from_slice(&8u32.to_le_bytes());
+        blob[24..28].copy_from_slice(&4u32.to_le_bytes());
+        assert!(PSF2Font::new(&blob).is_ok());
+    }
+
+    #[test]
+    fn glyph_at_fetches_by_index() {
+        for &(blob, _) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            let first = font.glyph_at(0).unwrap();
+            assert_eq!(first.count(), font.height as usize);
+            assert_eq!(first.width(), font.width as usize);
+            assert_eq!(font.glyph_at(b'A' as u32), font.glyph_for_utf8(b"A"));
+            assert_eq!(font.glyph_at(font.num_glyphs), None);
+        }
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn sequence_groups_dont_match_single_codepoints() {
+        // Glyph 1 has only a one-codepoint sequence, glyph 2 only `e` + U+0301.
+        let table = [
+            &[0xFF][..],
+            &[0xFE, 0xC3, 0xA9, 0xFF],
+            &[0xFE, b'e', 0xCC, 0x81, 0xFF],
+        ];
+        let blob = synthetic_font(32, 1, 3, 8, 1, &table.concat());
+        let mut font = PSF2Font::new(&blob).unwrap();
+
+        assert_eq!(font.get_glyph_data("é".as_bytes()), None);
+        assert_eq!(font.get_glyph_data("e\u{301}".as_bytes()), Some(&[2][..]));
+        assert_eq!(font.coverage(), 0);
+        assert_eq!(font.build_index(&mut []), 0);
+
+        let mut entries = font.unicode_entries();
+        assert_eq!(entries.next(), Some((1, "é".as_bytes())));
+        assert!(entries.in_sequence());
+    }
+
+    #[test]
+    fn hamming_distance_counts_differing_pixels() {
+        // 6 wide, so the low two bits of each byte are padding and must be ignored.
+        let a = [0b1010_1000, 0b0000_0000, 0b1111_1100];
+        let b = [0b1010_0111, 0b0100_0001, 0b1111_1110];
+        let (a, b) = (Glyph::new(&a, 6), Glyph::new(&b, 6));
+        assert_eq!(a.hamming_distance(&b), Some(3));
+        assert_eq!(b.hamming_distance(&a), Some(3));
+        assert_eq!(a.hamming_distance(&a), Some(0));
+
+        assert_eq!(a.hamming_distance(&Glyph::new(&[0; 3], 8)), None);
+        assert_eq!(a.hamming_distance(&Glyph::new(&[0; 2], 6)), None);
+    }
+
+    #[test]
+    fn glyph_row_sub_clips_columns() {
+        let data = [0b0010_1100];
+        let row = GlyphRow::new(&data, 7);
+        assert_eq!(row.sub(2, 5).collect::<Vec<_>>(), [true, false, true]);
+        assert_eq!(row.sub(2, 5).len(), 3);
+        assert_eq!(row.sub(4, 100).collect::<Vec<_>>(), [true, true, false]);
+        assert_eq!(row.sub(5, 3).count(), 0);
+
+        let mut advanced = row;
+        advanced.nth(5);
+        assert_eq!(advanced.sub(2, 5), row.sub(2, 5));
+    }
+
+    #[test]
+    fn reload_keeps_options_and_clears_the_cache() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        font.set_line_gap(2);
+        font.set_oob_policy(OobPolicy::Clamp);
+        font.glyph_for_char('é');
+        font.glyph_for_char('é');
+
+        assert_eq!(font.reload(b"not a font"), Err(PsfError::InvalidMagic));
+        assert_eq!(font.width, 8);
+
+        let (blob, _) = FONTS[3];
+        font.reload(blob).unwrap();
+        let fresh = PSF2Font::new(blob).unwrap();
+        assert_eq!((font.width, font.height), (12, 24));
+        assert_eq!(font.num_glyphs, fresh.num_glyphs);
+        assert_eq!(font.line_gap(), 2);
+        assert_eq!(font.oob_policy(), OobPolicy::Clamp);
+        assert_eq!(
+            font.glyph_by_idx(u32::MAX),
+            fresh.glyph_by_idx(fresh.num_glyphs - 1)
+        );
+        assert_eq!(font.cache_stats(), CacheStats::default());
+
+        // The 8x16 font's cached index for é must not leak into the 12x24 lookup.
+        assert_eq!(
+            font.glyph_for_char('é'),
+            PSF2Font::new(blob).unwrap().glyph_for_char('é')
+        );
+        if cfg!(not(feature = "ascii-only")) {
+            assert_eq!(font.cache_stats().misses, 1);
+        }
+    }
+
+    #[test]
+    fn from_parts_builds_a_font_from_separate_slices() {
+        let (blob, _) = FONTS[0];
+        let header = PSF2Header::from_bytes(blob).unwrap();
+        let glyphs_end = 32 + (header.num_glyphs * header.bytes_per_glyph) as usize;
+        // Copies, so nothing is contiguous with the header any more.
+        let glyphs = blob[32..glyphs_end].to_vec();
+        let unicode = blob[glyphs_end..].to_vec();
+
+        let mut parts = PSF2Font::from_parts(header, &glyphs, &unicode).unwrap();
+        let mut whole = PSF2Font::new(blob).unwrap();
+        for c in ['A', 'é', '±', '\u{2603}'] {
+            assert_eq!(parts.glyph_for_char(c), whole.glyph_for_char(c), "{c:?}");
+        }
+        assert_eq!(parts.fingerprint(), whole.fingerprint());
+
+        let short = &glyphs[..glyphs.len() - 1];
+        let err = PSF2Font::from_parts(header, short, &unicode).err();
+        assert_eq!(err, Some(PsfError::GlyphSizeMismatch));
+    }
+
+    #[test]
+    fn set_pixels_matches_full_scan() {
+        fn full_scan(glyph: Glyph) -> Vec<(usize, usize)> {
+            let mut lit = Vec::new();
+            for (y, row) in glyph.enumerate() {
+                lit.extend(row.enumerate().filter(|&(_, on)| on).map(|(x, _)| (x, y)));
+            }
+            lit
+        }
+
+        for &(blob, _) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            for c in ['A', 'g', '#', '@', ' '] {
+                let glyph = font.glyph_for_char(c).unwrap();
+                assert_eq!(
+                    glyph.set_pixels().collect::<Vec<_>>(),
+                    full_scan(glyph),
+                    "{c:?}"
+                );
+            }
+        }
+
+        // Padding bits are set in every byte here, and must not be reported.
+        let data = [0xFF; 4];
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let glyph = Glyph::new(&data, 11).bit_order(order);
+            let lit: Vec<_> = glyph.set_pixels().collect();
+            assert_eq!(lit.len(), 22);
+            assert_eq!(lit, full_scan(glyph));
+        }
+    }
+
+    #[test]
+    fn try_glyph_reports_why_a_lookup_missed() {
+        let mut font = PSF2Font::new(FONTS[0].0).unwrap();
+        assert_eq!(font.try_glyph(b"A").ok(), font.glyph_for_utf8(b"A"));
+        for bad in [&[0xC3][..], &[0xC0, 0x80], &[0xED, 0xA0, 0x80], &[0x80]] {
+            assert_eq!(
+                font.try_glyph(bad),
+                Err(LookupMiss::MalformedUtf8),
+                "{bad:02X?}"
+            );
+        }
+        assert_eq!(
+            font.try_glyph("\u{2603}".as_bytes()),
+            Err(LookupMiss::NotMapped)
+        );
+
+        // Without a table codepoints are indices, so U+0100 is past the last of 256 glyphs.
+        let blob = synthetic_font(32, 0, 256, 8, 8, &[]);
+        let mut direct = PSF2Font::new(&blob).unwrap();
+        let expected = if cfg!(feature = "ascii-only") {
+            LookupMiss::NotMapped
+        } else {
+            LookupMiss::IndexOutOfRange(0x100)
+        };
+        assert_eq!(direct.try_glyph("\u{100}".as_bytes()), Err(expected));
+        assert_eq!(direct.try_glyph(&[0xC4]), Err(LookupMiss::MalformedUtf8));
+        direct.set_oob_policy(OobPolicy::Clamp);
+        assert_eq!(
+            direct.try_glyph("\u{100}".as_bytes()).is_ok(),
+            cfg!(not(feature = "ascii-only"))
+        );
+    }
+
+    #[test]
+    fn blit_cell_fills_once_then_draws_lit_pixels() {
+        let data = [0b1000_0000, 0b0000_0000, 0b0110_0000];
+        let glyph = Glyph::new(&data, 3);
+        let (mut fills, mut lit) = (Vec::new(), Vec::new());
+        glyph.blit_cell(
+            |x, y, w, h| fills.push((x, y, w, h)),
+            |x, y| lit.push((x, y)),
+        );
+        assert_eq!(fills, [(0, 0, 3, 3)]);
+        assert_eq!(lit, [(0, 0), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn size_all_round_trips_through_its_index() {
+        assert_eq!(Size::ALL.len(), 6);
+        assert_eq!(Size::count(), 6);
+        for (i, &size) in Size::ALL.iter().enumerate() {
+            assert_eq!(Size::try_from(i), Ok(size));
+            assert_eq!(FONTS[i].1, size);
+        }
+        assert_eq!(Size::try_from(Size::count()), Err(PsfError::UnknownSize));
+    }
+
+    #[test]
+    fn blit_dyn_matches_generic_blit() {
+        let mut font = PSF2Font::new(FONTS[1].0).unwrap();
+        let glyph = font.glyph_for_utf8(b"R").unwrap();
+
+        let mut generic = Vec::new();
+        glyph.blit(|x, y, on| generic.push((x, y, on)));
+        assert_eq!(generic.len(), 6 * 12);
+
+        let mut on_stack = Vec::new();
+        glyph.blit_dyn(&mut |x, y, on| on_stack.push((x, y, on)));
+        assert_eq!(on_stack, generic);
+
+        let mut lit = 0;
+        let mut boxed: std::boxed::Box<dyn FnMut(usize, usize, bool)> =
+            std::boxed::Box::new(|_, _, on| lit += on as usize);
+        glyph.blit_dyn(&mut *boxed);
+        drop(boxed);
+        assert_eq!(lit, glyph.set_pixel_count());
+    }
+
+    #[test]
+    fn size_num_glyphs_matches_the_parsed_fonts() {
+        const ATLAS: [u8; Size::S8x16.num_glyphs() as usize] = [0; 512];
+        assert_eq!(ATLAS.len(), 512);
+
+        for &(blob, size) in FONTS {
+            let font = PSF2Font::new(blob).unwrap();
+            assert_eq!(size.num_glyphs(), font.num_glyphs, "{size}");
+        }
+    }
+
+    #[test]
+    fn glyph_by_idx_boundaries() {
+        for &(blob, size) in FONTS {
+            let font = PSF2Font::new(blob).unwrap();
+            let (n, bpg) = (font.num_glyphs, font.bytes_per_glyph as usize);
+            let header_size = font.header_size as usize;
+            let glyphs_end = header_size + n as usize * bpg;
+
+            let offset_in_blob = |data: &[u8]| data.as_ptr() as usize - blob.as_ptr() as usize;
+            let first = font.glyph_by_idx(0).unwrap();
+            assert_eq!(
+                (offset_in_blob(first), first.len()),
+                (header_size, bpg),
+                "{size}"
+            );
+            let last = font.glyph_by_idx(n - 1).unwrap();
+            assert_eq!(last.len(), bpg, "{size}");
+            assert_eq!(offset_in_blob(last) + last.len(), glyphs_end, "{size}");
+            assert_eq!(font.glyph_by_idx(n), None, "{size}");
+            assert_eq!(font.glyph_by_idx(u32::MAX), None, "{size}");
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn owned_rasters_match_the_iterators() {
+        let mut font = PSF2Font::new(FONTS[1].0).unwrap();
+        let glyph = font.glyph_for_utf8(b"W").unwrap();
+        let rows: Vec<bool> = glyph.flatten().collect();
+        assert_eq!(glyph.to_vec(), rows);
+        assert_eq!(glyph.to_vec().len(), 6 * 12);
+
+        let text = "ab\nc";
+        let (pixels, width, height) = font.rasterize_str_to_vec(text);
+        assert_eq!(
+            (width, height),
+            font.measure_str(text, &LayoutOptions::default())
+        );
+        let mut expected = std::vec![false; width * height];
+        font.render_str(text, &LayoutOptions::default(), |x, y| {
+            expected[y * width + x] = true;
+        });
+        assert_eq!(pixels, expected);
+        assert!(pixels.contains(&true));
+    }
+
+    #[test]
+    fn byte_wide_fonts_expose_rows_as_bytes() {
+        for &(blob, size) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            let byte_wide = size.dimensions().0 <= 8;
+            assert_eq!(font.is_byte_wide(), byte_wide, "{size}");
+
+            let glyph = font.glyph_for_utf8(b"M").unwrap();
+            let height = font.height as usize;
+            if byte_wide {
+                for (y, row) in glyph.row_bytes().enumerate() {
+                    assert_eq!(glyph.fast_row_u8(y), Some(row[0]), "{size}");
+                }
+            } else {
+                assert_eq!(glyph.fast_row_u8(0), None, "{size}");
+            }
+            assert_eq!(glyph.fast_row_u8(height), None, "{size}");
+        }
+    }
+
+    #[cfg(not(feature = "ascii-only"))]
+    #[test]
+    fn long_cache_keys_still_cache_without_clashing() {
+        let long = "é".repeat(10);
+        let similar = "é".repeat(9) + "è";
+        let emoji = "\u{1F600}";
+        let mut table = std::vec![0xFF];
+        table.extend_from_slice("é".as_bytes());
+        table.push(0xFF);
+        for seq in [&long, &similar] {
+            table.push(0xFE);
+            table.extend_from_slice(seq.as_bytes());
+            table.push(0xFF);
+        }
+        table.extend_from_slice(emoji.as_bytes());
+        table.push(0xFF);
+        let blob = synthetic_font(32, 1, 5, 8, 1, &table);
+        let mut font = PSF2Font::new(&blob).unwrap();
+
+        let keys = ["é", &long, &similar, emoji];
+        for _ in 0..2 {
+            for (glyph, key) in (1u8..).zip(keys) {
+                assert_eq!(
+                    font.get_glyph_data(key.as_bytes()),
+                    Some(&[glyph][..]),
+                    "{key}"
+                );
+            }
+        }
+        assert_eq!(font.cache_stats(), CacheStats { hits: 4, misses: 4 });
+
+        // Too long to cache at all, so every lookup misses, and nothing else is disturbed.
+        let huge = "é".repeat(200);
+        assert_eq!(font.get_glyph_data(huge.as_bytes()), None);
+        assert_eq!(font.get_glyph_data(huge.as_bytes()), None);
+        assert_eq!(font.cache_stats(), CacheStats { hits: 4, misses: 6 });
+        assert_eq!(font.get_glyph_data(long.as_bytes()), Some(&[2][..]));
+    }
+
+    #[test]
+    fn blend_goes_from_and_to_or() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        let a = font.glyph_for_utf8(b"O").unwrap();
+        let b = font.glyph_for_utf8(b"X").unwrap();
+        let pixels =
+            |rows: &mut dyn Iterator<Item = GlyphRow>| -> Vec<bool> { rows.flatten().collect() };
+
+        let and: Vec<bool> = a
+            .zip(b)
+            .flat_map(|(a, b)| a.zip(b).map(|(x, y)| x && y))
+            .collect();
+        let or: Vec<bool> = a
+            .zip(b)
+            .flat_map(|(a, b)| a.zip(b).map(|(x, y)| x || y))
+            .collect();
+        assert_eq!(pixels(&mut a.blend(&b, 0).unwrap()), and);
+        assert_eq!(pixels(&mut a.blend(&b, 255).unwrap()), or);
+
+        // Pixels only ever turn on as `t` grows.
+        let count = |t| {
+            pixels(&mut a.blend(&b, t).unwrap())
+                .iter()
+                .filter(|&&on| on)
+                .count()
+        };
+        let counts: Vec<usize> = (0..=255).step_by(16).map(count).collect();
+        assert!(counts.windows(2).all(|w| w[0] <= w[1]), "{counts:?}");
+        assert!(counts[0] < counts[8] && counts[8] < count(255));
+
+        let narrow = Glyph::new(&[0; 16], 4);
+        assert!(a.blend(&narrow, 128).is_none());
+    }
+
+    #[test]
+    fn scaled_xy_expands_each_axis() {
+        use core::num::NonZeroUsize;
+
+        let n = |n| NonZeroUsize::new(n).unwrap();
+        let data = [0b1010_0000, 0b0110_0000];
+        let glyph = Glyph::new(&data, 3);
+
+        assert_eq!(render(glyph.scaled_xy(n(2), n(1))), ["##..##", "..####"]);
+        assert_eq!(
+            render(glyph.scaled_xy(n(1), n(3))),
+            ["#.#", "#.#", "#.#", ".##", ".##", ".##"]
+        );
+        assert_eq!(
+            render(glyph.scaled(n(2))),
+            ["##..##", "##..##", "..####", "..####"]
+        );
+        assert_eq!(render(glyph.scaled(n(1))), render(glyph));
+
+        // A huge factor saturates and the row is capped instead of overflowing.
+        let cap = crate::glyph::MAX_ROW_BYTES * 8;
+        let row = glyph.scaled_xy(n(usize::MAX), n(1)).next().unwrap();
+        assert_eq!(row.len(), cap);
+        assert!(row.into_iter().all(|on| on));
+    }
+
+    #[test]
+    fn index_of_char_falls_back_to_notdef() {
+        let mut font = PSF2Font::new(FONTS[0].0).unwrap();
+        assert_eq!(font.notdef_index(), 0);
+        assert_eq!(font.index_of_char('A'), 'A' as u32);
+        // Private use, not in any Spleen font.
+        assert_eq!(font.index_of_char('\u{E000}'), font.notdef_index());
+        #[cfg(not(feature = "ascii-only"))]
+        {
+            let expected = font.scan_unicode_table("é".as_bytes()).unwrap();
+            assert_eq!(font.index_of_char('é'), expected);
+            // The second lookup comes from the cache.
+            assert_eq!(font.index_of_char('é'), expected);
+            assert_eq!(font.cache_stats().hits, 1);
+        }
+    }
+
+    #[test]
+    fn layout_positions_cells() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        font.set_line_gap(2);
+        let expected: Vec<_> = [('H', 0, 0), ('i', 8, 0), ('Y', 0, 18), ('o', 8, 18)]
+            .into_iter()
+            .map(|(c, x, y)| (x, y, font.glyph_for_char(c).unwrap()))
+            .collect();
+        let cells: Vec<_> = font
+            .layout("Hi\nYo")
+            .map(|cell| (cell.x, cell.y, cell.glyph))
+            .collect();
+        assert_eq!(cells, expected);
+
+        // Missing glyphs still take up their cell.
+        let xs: Vec<_> = font.layout("a\u{E000}b").map(|cell| cell.x).collect();
+        assert_eq!(xs, [0, 16]);
+    }
+
+    #[test]
+    fn layout_matches_render_str() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        for text in ["a\tb", "a\x07b\n\tc"] {
+            let mut rendered = Vec::new();
+            font.render_str(text, &LayoutOptions::default(), |x, y| {
+                rendered.push((x, y))
+            });
+            let mut laid_out = Vec::new();
+            for cell in font.layout(text) {
+                for (y, row) in cell.glyph.enumerate() {
+                    for (x, on) in row.enumerate() {
+                        if on {
+                            laid_out.push((cell.x + x, cell.y + y));
+                        }
+                    }
+                }
+            }
+            assert_eq!(laid_out, rendered, "{text:?}");
+        }
+        let xs: Vec<_> = font.layout("a\tb").map(|cell| cell.x).collect();
+        assert_eq!(xs, [0, 64]);
+    }
+
+    #[test]
+    fn oversized_glyph_counts_are_too_short() {
+        fn with_counts(num_glyphs: u32, bytes_per_glyph: u32) -> Vec<u8> {
+            let mut blob = synthetic_font(32, 0, 1, 8, 8, &[]);
+            blob[16..20].copy_from_slice(&num_glyphs.to_le_bytes());
+            blob[20..24].copy_from_slice(&bytes_per_glyph.to_le_bytes());
+            blob
+        }
+        // Products that wrap around in `u32` to a size the blob does have.
+        for (num_glyphs, bytes_per_glyph) in [
+            (0x1_0000, 0x1_0000),
+            (0x8000_0001, 2),
+            (u32::MAX, u32::MAX),
+            (2, 0x8000_0004),
+        ] {
+            let blob = with_counts(num_glyphs, bytes_per_glyph);
+            assert!(
+                matches!(PSF2Font::new(&blob), Err(PsfError::DataTooShort { .. })),
+                "{num_glyphs} x {bytes_per_glyph}"
+            );
+        }
+    }
+
+    #[test]
+    fn clones_have_their_own_cache() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        font.set_line_gap(3);
+        let text = if cfg!(feature = "ascii-only") {
+            "Az"
+        } else {
+            "Aé€"
+        };
+        font.prime_cache(text);
+        let mut copy = font.clone();
+
+        assert_eq!(copy.line_gap(), 3);
+        assert_eq!(copy.cache_stats(), CacheStats::default());
+        for c in text.chars() {
+            assert_eq!(copy.glyph_for_char(c), font.glyph_for_char(c));
+        }
+        // Only the original had the characters cached.
+        if !cfg!(feature = "ascii-only") {
+            assert_eq!(font.cache_stats().hits, 2);
+            assert_eq!(copy.cache_stats().misses, 2);
+        }
+        copy.glyph_for_char('ö');
+        assert_eq!(font.cache_stats().misses, 0);
+    }
+
+    #[test]
+    fn ram_footprint_is_the_struct_size() {
+        let font = PSF2Font::new(FONTS[0].0).unwrap();
+        assert_eq!(font.ram_footprint(), core::mem::size_of::<PSF2Font>());
+        // The crate docs promise the default cache fits in 2 KiB.
+        assert!(font.ram_footprint() < 2048, "{}", font.ram_footprint());
+
+        let small = PSF2Font::<8>::with_cache_size(FONTS[0].0).unwrap();
+        assert_eq!(small.ram_footprint(), core::mem::size_of::<PSF2Font<8>>());
+        assert!(small.ram_footprint() < font.ram_footprint());
+    }
+
+    #[test]
+    fn glyph_by_index_matches_char_lookup() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        let idx = font.index_of_char('A');
+        assert_eq!(font.glyph_by_index(idx), font.glyph_for_char('A'));
+        assert_eq!(font.glyph_by_index(idx).unwrap().width(), 8);
+        assert_eq!(font.glyph_by_index(font.num_glyphs), None);
+        // Nothing went through the cache.
+        assert_eq!(font.cache_stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn bold_smears_right_within_the_cell() {
+        let data = [0b1001_0000, 0b0011_1000];
+        let glyph = Glyph::new(&data, 5);
+        assert_eq!(render(glyph.bold()), ["##.##", "..###"]);
+    }
+
+    #[test]
+    fn styled_glyph_combines_attributes() {
+        // `blit` walks the same pixels as `rows`.
+        fn render_styled(styled: StyledGlyph<'_>) -> Vec<std::string::String> {
+            let mut rows = std::vec![std::string::String::new(); styled.glyph.height()];
+            styled.blit(|_, y, on| rows[y].push(if on { '#' } else { '.' }));
+            assert_eq!(rows, render(styled.rows()));
+            rows
+        }
+        let data = [0b0100_0000, 0b1000_0000, 0b0000_0000];
+        let glyph = Glyph::new(&data, 3);
+
+        let attrs = Attrs::BOLD | Attrs::UNDERLINE;
+        assert!(attrs.contains(Attrs::BOLD) && !attrs.contains(Attrs::INVERSE));
+        assert_eq!(
+            render_styled(StyledGlyph::new(glyph, attrs)),
+            [".##", "##.", "###"]
+        );
+        assert_eq!(
+            render_styled(StyledGlyph::new(glyph, attrs | Attrs::INVERSE)),
+            ["#..", "..#", "..."]
+        );
+        // Blink doesn't change the bitmap.
+        assert_eq!(
+            render_styled(StyledGlyph::new(glyph, Attrs::BLINK)),
+            render_styled(StyledGlyph::new(glyph, Attrs::NONE))
+        );
+    }
+
+    #[test]
+    fn header_bytes_round_trip() {
+        let header = PSF2Header::new(PSF2_MAGIC, 0, 32, 1, 300, 24, 12, 9);
+        let bytes = header.to_bytes();
+        assert_eq!(PSF2Header::from_bytes(&bytes).unwrap(), header);
+        assert_eq!(&bytes[16..20], &300u32.to_le_bytes());
+
+        // A parsed header serializes to the bytes it came from.
+        let blob = FONTS[0].0;
+        let parsed = PSF2Header::from_bytes(blob).unwrap();
+        assert_eq!(parsed.to_bytes(), blob[..32]);
+    }
+
+    #[test]
+    fn new_no_unicode_maps_codepoints_to_indices() {
+        let blob = FONTS[0].0;
+        let mut plain = PSF2Font::new_no_unicode(blob).unwrap();
+        let mut full = PSF2Font::new(blob).unwrap();
+        assert!(plain.unicode_table().is_empty());
+        assert_eq!(plain.glyph_for_char('A'), full.glyph_for_char('A'));
+        assert_eq!(plain.glyph_at(300), full.glyph_at(300));
+
+        // A character the table maps, but whose codepoint is past the last glyph.
+        #[cfg(not(feature = "ascii-only"))]
+        {
+            let c = full
+                .unicode_entries()
+                .filter_map(|(_, entry)| core::str::from_utf8(entry).ok()?.chars().next())
+                .find(|&c| c as u32 >= full.num_glyphs)
+                .unwrap();
+            assert!(full.glyph_for_char(c).is_some());
+            let mut buf = [0u8; 4];
+            assert_eq!(
+                plain.try_glyph(c.encode_utf8(&mut buf).as_bytes()),
+                Err(LookupMiss::IndexOutOfRange(c as u32))
+            );
+        }
+        assert_eq!(plain.cache_stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn glyph_row_bytes_match_the_source() {
+        let (blob, _) = FONTS[3];
+        let mut font = PSF2Font::new(blob).unwrap();
+        let data = font.get_glyph_data(b"A").unwrap();
+        let glyph = font.glyph_for_char('A').unwrap();
+        let stride = glyph.stride();
+        assert_eq!(stride, 2);
+        for (y, mut row) in glyph.enumerate() {
+            assert_eq!(row.width(), 12);
+            assert_eq!(row.bytes(), &data[y * stride..(y + 1) * stride]);
+            row.next();
+            assert_eq!(row.bytes().len(), stride);
+        }
+
+        // Computed rows are trimmed to their width.
+        let inverted = Glyph::new(&[0x0F], 3).inverted().next().unwrap();
+        assert_eq!(inverted.bytes(), [0b1110_0000]);
+    }
+
+    #[test]
+    fn cell_overlap_shortens_the_advance() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        let options = LayoutOptions::default();
+        let text = if cfg!(feature = "ascii-only") {
+            "---"
+        } else {
+            "─┬─"
+        };
+        font.set_cell_overlap(1);
+        assert_eq!(font.cell_advance(), 7);
+        assert_eq!(font.measure_str(text, &options), (3 * 8 - 2, 16));
+        assert_eq!(font.measure_str("", &options).0, 0);
+
+        let mut plotted = [[false; 22]; 16];
+        font.render_str(text, &options, |x, y| plotted[y][x] = true);
+        let xs: Vec<_> = font.layout(text).map(|cell| cell.x).collect();
+        assert_eq!(xs, [0, 7, 14]);
+
+        // Overlapping columns are OR-ed, so the line doesn't break where cells meet.
+        let mut out = [0u8; 3 * 16];
+        assert_eq!(font.rasterize_line(text, &mut out, 24), Ok((22, 16)));
+        for (y, row) in plotted.iter().enumerate() {
+            for (x, &on) in row.iter().enumerate() {
+                assert_eq!(
+                    out[y * 3 + x / 8] & (0x80 >> (x % 8)) != 0,
+                    on,
+                    "({x}, {y})"
+                );
+            }
+        }
+
+        // At least one column always advances.
+        font.set_cell_overlap(100);
+        assert_eq!(font.cell_advance(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "raster")]
+    fn resample_spreads_dropped_lines() {
+        let mut font = PSF2Font::new(FONTS[3].0).unwrap();
+        let glyph = font.glyph_for_char('W').unwrap();
+        let (w, h) = glyph.dimensions();
+        let pixels = |glyph: Glyph<'_>| -> Vec<u8> {
+            glyph
+                .flat_map(|row| row.map(|on| if on { 255 } else { 0 }))
+                .collect()
+        };
+
+        let mut same = std::vec![7u8; w * h];
+        glyph.resample(w, h, &mut same, w, 0, 0);
+        assert_eq!(same, pixels(glyph));
+
+        // Halving keeps the even rows and columns, so every other one is dropped.
+        let mut half = std::vec![7u8; w / 2 * h / 2];
+        glyph.resample(w / 2, h / 2, &mut half, w / 2, 0, 0);
+        let expected: Vec<u8> = (0..h / 2)
+            .flat_map(|y| (0..w / 2).map(move |x| (x, y)))
+            .map(|(x, y)| same[2 * y * w + 2 * x])
+            .collect();
+        assert_eq!(half, expected);
+
+        // 24 rows into 20: the 4 dropped rows are evenly apart.
+        let mut sampled = [0usize; 20];
+        let data: Vec<u8> = (0..24u8).collect();
+        let ramp = Glyph::new(&data, 8);
+        let mut out = [0u8; 8 * 20];
+        ramp.resample(8, 20, &mut out, 8, 0, 0);
+        for (y, row) in out.chunks(8).enumerate() {
+            let byte = row.iter().fold(0u8, |b, &px| b << 1 | (px != 0) as u8);
+            sampled[y] = byte as usize;
+        }
+        let dropped: Vec<usize> = (0..24).filter(|r| !sampled.contains(r)).collect();
+        assert_eq!(dropped, [5, 11, 17, 23]);
+    }
+
+    #[test]
+    fn glyph_height_comes_from_the_header() {
+        for (blob, _) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            let height = font.height as usize;
+            for glyph in [font.glyph_for_char('A'), font.glyph_at(0)] {
+                let glyph = glyph.unwrap();
+                assert_eq!(glyph.height(), height);
+                assert_eq!(glyph.count(), height);
+            }
+        }
+
+        // Padding after the bitmap isn't a row, and a short slice only has its whole rows.
+        let data = [0xFF; 7];
+        assert_eq!(Glyph::with_height(&data, 8, 4).height(), 4);
+        assert_eq!(Glyph::with_height(&data, 8, 4).rev().count(), 4);
+        assert_eq!(Glyph::with_height(&data, 12, 5).height(), 3);
+        assert_eq!(Glyph::with_height(&data, 0, 5).height(), 0);
+        let mut glyph = Glyph::with_height(&data, 8, 4);
+        assert!(glyph.nth(1).is_some());
+        assert_eq!(glyph.len(), 2);
+
+        let header = PSF2Header::new(PSF2_MAGIC, 0, 32, 0, 2, 4, 3, 8);
+        let font = PSF2Font::from_parts(header, &[0xFF; 8], &[]).unwrap();
+        assert_eq!(font.glyph_at(1).unwrap().height(), 3);
+    }
+
+    #[test]
+    fn glyph_str_takes_one_char() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        assert_eq!(font.glyph_str("A"), font.glyph_for_char('A'));
+        assert!(font.glyph_str("A").is_some());
+        if !cfg!(feature = "ascii-only") {
+            assert_eq!(font.glyph_str("é"), font.glyph_for_char('é'));
+        }
+        assert_eq!(font.glyph_str(""), None);
+        assert_eq!(font.glyph_str("AB"), None);
+        assert_eq!(font.glyph_str("e\u{301}"), None);
+    }
+
+    #[test]
+    fn full_render_holds_debug_invariants() {
+        // Every glyph and pixel of every font, through the debug assertions on the way.
+        for (blob, _) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            font.set_oob_policy(OobPolicy::Clamp);
+            for idx in 0..=font.num_glyphs {
+                for mut row in font.glyph_at(idx).unwrap() {
+                    while row.next().is_some() {}
+                    assert_eq!(row.next(), None);
+                }
+            }
+            let text = "hello, world\n\tline two";
+            let (w, h) = font.measure_str(text, &LayoutOptions::default());
+            font.render_str(text, &LayoutOptions::default(), |x, y| {
+                assert!(x < w && y < h);
+            });
+        }
+    }
+
+    #[test]
+    fn trailing_bytes_after_the_table_are_ignored() {
+        let table = [b'A', 0xFF, 0xC3, 0xA9, 0xFF];
+        let mut blob = synthetic_font(32, 1, 2, 8, 2, &table);
+        // Padding, then bytes that would map 'Z' and '€' to further records.
+        blob.extend_from_slice(&[0x00, 0x00, b'Z', 0xFF, 0xE2, 0x82, 0xAC, 0xFF, 0xFF]);
+        let mut font = PSF2Font::new(&blob).unwrap();
+
+        if !cfg!(feature = "ascii-only") {
+            assert_eq!(font.unicode_table(), table);
+            assert_eq!(font.get_glyph_data("é".as_bytes()), Some(&[1u8, 1][..]));
+            assert_eq!(font.get_glyph_data("€".as_bytes()), None);
+            assert_eq!(font.unicode_entries().count(), 2);
+            assert_eq!(font.get_glyph_data(b"A"), Some(&[0u8, 0][..]));
+        }
+        assert_eq!(font.get_glyph_data(b"Z"), None);
+
+        // PSF1: the UCS-2 table ends after the 256th `FFFF`.
+        let mut psf1 = std::vec![0x36, 0x04, 0x02, 1];
+        psf1.extend(0..=255u8);
+        for glyph in 0..256u16 {
+            psf1.extend_from_slice(&glyph.to_le_bytes());
+            psf1.extend_from_slice(&[0xFF, 0xFF]);
+        }
+        let len = psf1.len();
+        psf1.extend_from_slice(&[0xAC, 0x20, 0xFF, 0xFF]);
+        let font = PSF2Font::new(&psf1).unwrap();
+        if !cfg!(feature = "ascii-only") {
+            assert_eq!(font.unicode_table().len(), len - 4 - 256);
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "ascii-only"))]
+    fn codepoints_past_the_table_skip_the_scan() {
+        use crate::psf::TABLE_SCANS;
+
+        fn scans(font: &mut PSF2Font<'_>, c: char) -> (bool, u32) {
+            TABLE_SCANS.with(|n| n.set(0));
+            let found = font.glyph_for_char(c).is_some();
+            (found, TABLE_SCANS.with(|n| n.get()))
+        }
+        for (blob, _) in FONTS {
+            let mut font = PSF2Font::new(blob).unwrap();
+            assert_eq!(scans(&mut font, '😀'), (false, 0));
+            assert_eq!(scans(&mut font, 'é'), (true, 1));
+        }
+
+        // Only single codepoints count: a sequence entry doesn't widen the span.
+        let table = [b'a', 0xFF, b'b', 0xFE, 0xE2, 0x82, 0xAC, 0xFF];
+        let blob = synthetic_font(32, 1, 2, 8, 2, &table);
+        let mut font = PSF2Font::new(&blob).unwrap();
+        assert_eq!(scans(&mut font, '€'), (false, 0));
+        assert_eq!(scans(&mut font, 'A'), (false, 0));
+        assert_eq!(font.get_glyph_data("€".as_bytes()), None);
+        assert_eq!(font.get_glyph_data(b"b"), Some(&[1u8, 1][..]));
+    }
+
+    #[test]
+    fn region_offsets_match_the_layout() {
+        let (blob, size) = FONTS[2];
+        assert_eq!(size, Size::S8x16);
+        let font = PSF2Font::new(blob).unwrap();
+        // PSF1: a 4-byte header, then 512 glyphs of 16 bytes.
+        assert_eq!(font.glyphs_offset(), 4);
+        assert_eq!(font.unicode_offset(), 4 + 512 * 16);
+        let a = font.glyphs_offset() + 'A' as usize * 16;
+        assert_eq!(font.glyph_by_idx('A' as u32), Some(&blob[a..a + 16]));
+        if !cfg!(feature = "ascii-only") {
+            assert_eq!(font.unicode_table(), &blob[font.unicode_offset()..]);
+        }
+
+        let blob = FONTS[0].0;
+        let font = PSF2Font::new(blob).unwrap();
+        let header = PSF2Header::from_bytes(blob).unwrap();
+        assert_eq!(font.glyphs_offset(), header.header_size as usize);
+        assert_eq!(
+            font.unicode_offset(),
+            (header.header_size + header.num_glyphs * header.bytes_per_glyph) as usize
+        );
+    }
+
+    #[test]
+    fn rows_and_cols_at_screen_coordinates() {
+        let data = [0b1000_0000, 0b0100_0000, 0b0010_0000];
+        let glyph = Glyph::new(&data, 3);
+        let ys: Vec<_> = glyph.rows_at(10).map(|(y, _)| y).collect();
+        assert_eq!(ys, [10, 11, 12]);
+
+        let lit: Vec<_> = glyph
+            .rows_at(10)
+            .flat_map(|(y, row)| {
+                row.cols_at(5)
+                    .filter(|&(_, on)| on)
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect();
+        assert_eq!(lit, [(5, 10), (6, 11), (7, 12)]);
+
+        // Clipped or partly iterated rows keep their columns.
+        let row = glyph.row(2).unwrap();
+        assert_eq!(
+            row.sub(1, 3).cols_at(5).collect::<Vec<_>>(),
+            [(6, false), (7, true)]
+        );
+        let mut row = glyph.row(0).unwrap();
+        row.next();
+        assert_eq!(row.cols_at(0).next(), Some((1, false)));
+        let mut rows = glyph;
+        rows.next();
+        assert_eq!(rows.rows_at(0).next().map(|(y, _)| y), Some(0));
+
+        // Coordinates stop at the edge of `usize` instead of overflowing.
+        let ys: Vec<_> = glyph.rows_at(usize::MAX - 1).map(|(y, _)| y).collect();
+        assert_eq!(ys, [usize::MAX - 1, usize::MAX]);
+        let xs: Vec<_> = row.cols_at(usize::MAX - 1).map(|(x, _)| x).collect();
+        assert_eq!(xs, [usize::MAX]);
+    }
+
+    #[test]
+    fn data_too_short_reports_the_missing_bytes() {
+        let blob = synthetic_font(32, 0, 4, 8, 8, &[]);
+        assert_eq!(blob.len(), 32 + 4 * 8);
+        let short = &blob[..blob.len() - 1];
+        let Err(err) = PSF2Font::new(short) else {
+            panic!("parsed a truncated font");
+        };
+        assert_eq!(
+            err,
+            PsfError::DataTooShort {
+                needed: 64,
+                got: 63
+            }
+        );
+        assert_eq!(
+            std::format!("{err}"),
+            "PSF2 data too short: needed 64 bytes, got 63"
+        );
+    }
+
+    #[test]
+    fn render_u64_draws_digit_cells() {
+        fn blit(glyph: Glyph<'_>, cell: usize, out: &mut Vec<(usize, usize, usize, bool)>) {
+            glyph.blit(|x, y, on| out.push((cell, cell * glyph.width() + x, y, on)));
+        }
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        let mut expected = Vec::new();
+        blit(font.glyph_for_char('4').unwrap(), 0, &mut expected);
+        blit(font.glyph_for_char('2').unwrap(), 1, &mut expected);
+
+        let mut pixels = Vec::new();
+        let size = font.render_u64(42, |cell, x, y, on| pixels.push((cell, x, y, on)));
+        assert_eq!(size, (16, 16));
+        assert_eq!(pixels, expected);
+
+        // Zero padding draws a `0` glyph in each leading cell.
+        let lit = |font: &mut PSF2Font<'_>, value, width, cell| {
+            let mut lit = Vec::new();
+            font.render_u64_padded(value, width, Padding::Zero, |c, x, y, on| {
+                if c == cell && on {
+                    lit.push((x % 8, y));
+                }
+            });
+            lit
+        };
+        assert_eq!(lit(&mut font, 7, 3, 0), lit(&mut font, 0, 0, 0));
+        assert_eq!(lit(&mut font, 7, 3, 1), lit(&mut font, 0, 0, 0));
+        assert_eq!(lit(&mut font, 7, 3, 2), lit(&mut font, 7, 0, 0));
+
+        let size = |font: &mut PSF2Font<'_>, value, width, pad| {
+            font.render_u64_padded(value, width, pad, |_, _, _, _| {})
+        };
+        assert_eq!(size(&mut font, u64::MAX, 0, Padding::Zero), (20 * 8, 16));
+        assert_eq!(size(&mut font, 5, 4, Padding::Space), (4 * 8, 16));
+        assert_eq!(size(&mut font, 12345, 2, Padding::Space), (5 * 8, 16));
+    }
+
+    #[test]
+    fn render_hex_and_bin_match_formatted_text() {
+        // Each `render_*` call against `render_str` of the same digits.
+        fn lit(
+            f: impl FnOnce(&mut dyn FnMut(usize, usize, usize, bool)) -> (usize, usize),
+        ) -> Vec<(usize, usize)> {
+            let mut lit = Vec::new();
+            f(&mut |_, x, y, on| {
+                if on {
+                    lit.push((x, y));
+                }
+            });
+            lit.sort();
+            lit
+        }
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        let text = |font: &mut PSF2Font<'_>, s: &str| {
+            let mut lit = Vec::new();
+            font.render_str(s, &LayoutOptions::default(), |x, y| lit.push((x, y)));
+            lit.sort();
+            lit
+        };
+
+        let expected = text(&mut font, "DEADBEEF");
+        assert_eq!(lit(|f| font.render_hex(0xDEADBEEF, true, f)), expected);
+        let expected = text(&mut font, "deadbeef");
+        assert_eq!(lit(|f| font.render_hex(0xDEADBEEF, false, f)), expected);
+        let expected = text(&mut font, "000000ff");
+        assert_eq!(
+            lit(|f| font.render_hex_padded(0xFF, false, 8, Padding::Zero, f)),
+            expected
+        );
+        let expected = text(&mut font, "101");
+        assert_eq!(lit(|f| font.render_bin(5, f)), expected);
+        let expected = text(&mut font, "00000101");
+        assert_eq!(
+            lit(|f| font.render_bin_padded(5, 8, Padding::Zero, f)),
+            expected
+        );
+        assert_eq!(font.render_bin(u64::MAX, |_, _, _, _| {}).0, 64 * 8);
+        assert_eq!(font.render_hex(0, true, |_, _, _, _| {}).0, 8);
+    }
+
+    #[test]
+    fn cropped_keeps_only_the_ink_box() {
+        let data = [0x00, 0b0010_0000, 0b0001_1000, 0b0010_0000, 0x00];
+        let glyph = Glyph::new(&data, 8);
+        assert_eq!(glyph.ink_bounds(), Some((2, 1, 3, 3)));
+        assert_eq!(render(glyph.cropped()), ["#..", ".##", "#.."]);
+
+        let blank = Glyph::new(&[0u8; 4], 8);
+        assert_eq!(blank.ink_bounds(), None);
+        assert_eq!(blank.cropped().count(), 0);
+
+        // Every set pixel of a real glyph survives, shifted to the box's origin.
+        let mut font = PSF2Font::new(FONTS[3].0).unwrap();
+        let glyph = font.glyph_for_char('g').unwrap();
+        let (x0, y0, w, h) = glyph.ink_bounds().unwrap();
+        let cropped: Vec<_> = glyph.cropped().collect();
+        assert_eq!(cropped.len(), h);
+        assert!(cropped.iter().all(|row| row.len() == w));
+        let moved: Vec<_> = glyph.set_pixels().map(|(x, y)| (x - x0, y - y0)).collect();
+        let kept: Vec<_> = cropped
+            .into_iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.enumerate()
+                    .filter(|&(_, on)| on)
+                    .map(move |(x, _)| (x, y))
+            })
+            .collect();
+        assert_eq!(kept, moved);
+    }
+
+    #[test]
+    fn random_blobs_never_panic() {
+        // xorshift64: deterministic, so a failure reproduces.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+        let chars = ['A', ' ', '\u{7F}', 'é', '€', '\u{FFFD}', '😀', '\u{10FFFF}'];
+        let mut parsed = 0;
+        for _ in 0..3000 {
+            let mut blob: Vec<u8> = (0..next(400)).map(|_| next(256) as u8).collect();
+            match next(3) {
+                // A PSF2 header with small, sometimes inconsistent, fields.
+                0 => {
+                    let width = next(40) as u32;
+                    let height = next(12) as u32;
+                    let bpg = match next(2) {
+                        0 => width.div_ceil(8) * height,
+                        _ => next(64) as u32,
+                    };
+                    let fields = [0, 32 + next(8) as u32, next(2) as u32, next(10) as u32];
+                    let mut header = PSF2_MAGIC.to_vec();
+                    for field in fields.into_iter().chain([bpg, height, width]) {
+                        header.extend_from_slice(&field.to_le_bytes());
+                    }
+                    blob.splice(0..0, header);
+                }
+                1 => {
+                    blob.splice(0..0, [0x36, 0x04, next(4) as u8, next(20) as u8]);
+                }
+                _ => {}
+            }
+            let Ok(mut font) = PSF2Font::<4>::with_cache_size(&blob) else {
+                continue;
+            };
+            parsed += 1;
+            font.set_oob_policy(
+                [OobPolicy::None, OobPolicy::Clamp, OobPolicy::Replacement][next(3) as usize],
+            );
+            for &c in &chars {
+                if let Some(glyph) = font.glyph_for_char(c) {
+                    glyph.blit(|_, _, _| {});
+                    glyph.rev().for_each(|row| row.for_each(drop));
+                }
+                font.index_of_char(c);
+            }
+            for _ in 0..8 {
+                let key: Vec<u8> = (0..next(6)).map(|_| next(256) as u8).collect();
+                let _ = font.try_glyph(&key);
+                font.glyph_at(next(20) as u32);
+            }
+            font.unicode_entries().for_each(drop);
+            font.render_str("Hi\té\n€x", &LayoutOptions::default(), |_, _| {});
+        }
+        assert!(parsed > 100, "only {parsed} blobs parsed");
+
+        // Rows built from slices too short for their width read as clear.
+        let row = GlyphRow::new(&[0xFF], 20);
+        assert_eq!(row.filter(|&on| on).count(), 8);
+    }
+
+    #[test]
+    fn glyph_or_replacement_walks_the_candidates() {
+        // Glyph 0 maps 'a', glyph 1 '.', glyph 2 'z'.
+        let table = [b'a', 0xFF, b'.', 0xFF, b'z', 0xFF];
+        let blob = synthetic_font(32, 1, 3, 8, 2, &table);
+        let mut font = PSF2Font::new(&blob).unwrap();
+        let glyph = [font.glyph_at(0), font.glyph_at(1), font.glyph_at(2)];
+
+        // Nothing maps '#', but the default candidates aren't there either: notdef.
+        assert_eq!(font.glyph_or_replacement('#'), glyph[0]);
+        if !cfg!(feature = "ascii-only") {
+            assert_eq!(font.glyph_or_replacement('z'), glyph[2]);
+            // Only the third candidate exists.
+            font.set_fallback_candidates(&['\u{2022}', '*', '.']);
+            assert_eq!(font.glyph_or_replacement('#'), glyph[1]);
+        }
+        font.set_fallback_candidates(&[]);
+        assert_eq!(font.glyph_or_replacement('#'), glyph[0]);
+
+        // The bundled fonts have U+FFFD or '?'.
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        assert!(font.glyph_for_char('\u{E000}').is_none());
+        let fallback = font.glyph_or_replacement('\u{E000}');
+        assert!(fallback.is_some() && fallback != font.glyph_at(0));
+    }
+
+    #[test]
+    fn glyph_for_codepoint_validates_scalars() {
+        let mut font = PSF2Font::new(FONTS[2].0).unwrap();
+        assert_eq!(font.glyph_for_codepoint(0x41), font.glyph_for_char('A'));
+        assert!(font.glyph_for_codepoint(0x41).is_some());
+        if !cfg!(feature = "ascii-only") {
+            assert_eq!(font.glyph_for_codepoint(0xE9), font.glyph_for_char('é'));
+            assert!(font.glyph_for_codepoint(0x2500).is_some());
+        }
+        assert_eq!(font.glyph_for_codepoint(0xD800), None);
+        assert_eq!(font.glyph_for_codepoint(0xDFFF), None);
+        assert_eq!(font.glyph_for_codepoint(0x11_0000), None);
+    }
+
+    #[test]
+    fn repeated_keys_hit_in_one_comparison() {
+        use crate::cache::{Cache, KEY_COMPARISONS};
+
+        fn comparisons(cache: &mut Cache, key: &[u8]) -> (Option<u32>, u32) {
+            KEY_COMPARISONS.with(|n| n.set(0));
+            let found = cache.get(key);
+            (found, KEY_COMPARISONS.with(|n| n.get()))
+        }
+        let mut cache: Cache = Cache::new();
+        let keys: Vec<[u8; 2]> = (0..48u8).map(|i| [0xC4, 0x80 + i]).collect();
+        for (offset, key) in keys.iter().enumerate() {
+            cache.insert(key, offset as u32);
+        }
+
+        // Key 0 shares its index slot with later keys, so the first lookup scans the ring...
+        let (found, first) = comparisons(&mut cache, &keys[0]);
+        assert_eq!(found, Some(0));
+        assert!(first > 1, "{first}");
+        // ...and moves it into the slot, so repeating it takes one comparison.
+        assert_eq!(comparisons(&mut cache, &keys[0]), (Some(0), 1));
+        assert_eq!(comparisons(&mut cache, &keys[0]), (Some(0), 1));
+        assert_eq!(comparisons(&mut cache, &keys[47]), (Some(47), 1));
+
+        // A slot whose entry was overwritten falls back to the scan instead of a wrong hit.
+        let mut small = Cache::<2>::new();
+        small.insert(b"x", 1);
+        small.insert(b"y", 2);
+        small.insert(b"z", 3);
+        assert_eq!(small.get(b"x"), None);
+        assert_eq!(small.get(b"z"), Some(3));
+        assert_eq!(small.get(b"y"), Some(2));
+        assert_eq!(Cache::<0>::new().get(b"x"), None);
+    }
+
+    #[test]
+    fn has_unicode_table_reports_the_table() {
+        for (blob, _) in FONTS {
+            let font = PSF2Font::new(blob).unwrap();
+            assert_eq!(font.has_unicode_table(), !cfg!(feature = "ascii-only"));
+            assert!(!PSF2Font::new_no_unicode(blob).unwrap().has_unicode_table());
+        }
+        let blob = synthetic_font(32, 0, 4, 8, 8, &[]);
+        assert!(!PSF2Font::new(&blob).unwrap().has_unicode_table());
+        // The flag alone doesn't make a table.
+        let blob = synthetic_font(32, 1, 4, 8, 8, &[]);
+        assert!(!PSF2Font::new(&blob).unwrap().has_unicode_table());
+    }
+
+    #[test]
+    fn huge_widths_do_not_overflow() {
+        let glyph = Glyph::new(&[0xFF; 4], usize::MAX);
+        assert_eq!(glyph.stride(), usize::MAX.div_ceil(8));
+        assert_eq!(glyph.row(0), None);
+        assert_eq!(glyph.count(), 0);
+
+        let row = GlyphRow::new(&[0xFF], usize::MAX);
+        // Only the 8 pixels actually backed by the slice are set.
+        assert_eq!(row.take(9).filter(|&on| on).count(), 8);
+    }
 }
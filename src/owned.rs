@@ -0,0 +1,28 @@
+//! Owned rasters for targets with an allocator.
+
+use alloc::vec::Vec;
+
+use crate::glyph::Glyph;
+use crate::layout::LayoutOptions;
+use crate::psf::PSF2Font;
+
+impl Glyph<'_> {
+    /// The remaining rows as one row-major buffer, `width` pixels per row.
+    pub fn to_vec(&self) -> Vec<bool> {
+        let mut pixels = Vec::with_capacity(self.width() * self.height());
+        pixels.extend(self.flatten());
+        pixels
+    }
+}
+
+impl<const CACHE: usize> PSF2Font<'_, CACHE> {
+    /// Renders `text` like [`render_str`](Self::render_str) with the default
+    /// [`LayoutOptions`], returning the row-major buffer with its width and height.
+    pub fn rasterize_str_to_vec(&mut self, text: &str) -> (Vec<bool>, usize, usize) {
+        let options = LayoutOptions::default();
+        let (width, height) = self.measure_str(text, &options);
+        let mut pixels = alloc::vec![false; width * height];
+        self.render_str(text, &options, |x, y| pixels[y * width + x] = true);
+        (pixels, width, height)
+    }
+}
@@ -9,13 +9,24 @@
 /// 3. Store Unicode information/offsets
 ///     - Data starts at header_size + num_glyphs * bytes_per_glyph offset.
 ///     - Unicode characters are mapped to glyph indices; each "line" ends in 0xFF.
-use crate::cache::Cache;
+use crate::cache::{Cache, CacheStats, DEFAULT_CACHE_SIZE};
+use crate::error::{LookupMiss, PsfError};
 use crate::glyph::Glyph;
+use crate::layout::{COMBINING_MARKS, FALLBACK_CHAR, REPLACEMENT_CHAR};
+use crate::utf8::{decode_utf8, is_complete_utf8, utf8_seq_len};
+use crate::writer::{write_header, END_REC, HEADER_SIZE};
+use core::ops::RangeInclusive;
 
 /// The magic number for PSF2 fonts is stored from LSB to MSB
 pub const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
 
-#[derive(Debug, Clone, Copy)]
+/// PSF1 `mode` bits: 512 glyphs instead of 256, a unicode table, and a table that may
+/// hold sequences (which implies a table, even without `PSF1_MODEHASTAB`).
+const PSF1_MODE512: u8 = 0x01;
+const PSF1_MODEHASTAB: u8 = 0x02;
+const PSF1_MODEHASSEQ: u8 = 0x04;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PSF2Header {
     /// The magic number for PSF2; see above
     pub magic: [u8; 4],
@@ -35,7 +46,15 @@ pub struct PSF2Header {
     pub width: u32,
 }
 
-pub struct PSF2Font<'a> {
+/// A parsed font. `CACHE` is the number of lookups remembered by the glyph cache
+/// (see [`Cache`](crate::cache)); `0` disables caching and saves its RAM.
+///
+/// A font only borrows its data, so it is `Send` and `Sync`, and a
+/// `PSF2Font<'static>` over a bundled font (see [`PSF2Font::from_static`]) can live in a
+/// `static`. Lookups still take `&mut self` because they update the cache: share one
+/// behind a lock, or use `SyncPSF2Font` (with the `sync` feature), which
+/// takes `&self`.
+pub struct PSF2Font<'a, const CACHE: usize = DEFAULT_CACHE_SIZE> {
     /// Height of each glyph in pixels.
     pub height: u32,
     /// Width of each glyph in pixels.
@@ -50,41 +69,218 @@ pub struct PSF2Font<'a> {
     glyphs: &'a [u8],
     /// Indices of Unicode characters mapped to glyph data.
     unicode_mapping: &'a [u8],
+    /// Whether printable ASCII byte `b` lives at glyph index `b`, enabling the ASCII fast path.
+    ascii_direct: bool,
     /// Cache for glyph indices.
-    cache: Cache,
+    cache: Cache<CACHE>,
+    /// What `glyph_by_idx` does with out-of-range indices.
+    oob_policy: OobPolicy,
+    /// The in-range index `oob_policy` resolves to, if any.
+    oob_fallback: Option<u32>,
+    /// Extra blank pixels between lines in layout.
+    line_gap: usize,
+    /// Pixels adjacent cells overlap by in layout.
+    cell_overlap: usize,
+    /// Codepoints that take no advance in layout.
+    zero_width: &'a [RangeInclusive<char>],
+    /// `(codepoint, glyph)` pairs sorted for binary search; empty unless `build_index` ran.
+    index: &'a [(u32, u32)],
+    /// Tried in order by `glyph_or_replacement` for characters without a glyph.
+    fallback_candidates: &'static [char],
+    /// Lowest and highest single codepoint in the unicode table; `(u32::MAX, 0)` if none.
+    codepoint_span: (u32, u32),
+}
+
+/// What [`PSF2Font::glyph_by_idx`] (and so every lookup) returns for an index `>= num_glyphs`.
+///
+/// Indices read from a unicode table are always in range, since records are counted
+/// against `num_glyphs`; out-of-range indices come from fonts without a table (where a
+/// codepoint is its own index) or from callers passing indices directly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OobPolicy {
+    /// No glyph; the lookup returns `None`.
+    #[default]
+    None,
+    /// The last glyph of the font.
+    Clamp,
+    /// The font's glyph for [`REPLACEMENT_CHAR`], or for [`FALLBACK_CHAR`] if it has none.
+    /// If it has neither, lookups return `None` as with [`OobPolicy::None`].
+    Replacement,
 }
 
 impl<'a> PSF2Font<'a> {
     /// Creates a new PSF2 font from a byte slice.
     /// Parses out header and validates, populates glyph and unicode mapping data.
-    pub fn new(data: &'a [u8]) -> Result<Self, &'static str> {
+    ///
+    /// This is a `const fn`; see [`PSF2Font::new_const`] for parsing a font at compile time.
+    pub const fn new(data: &'a [u8]) -> Result<Self, PsfError> {
+        Self::with_cache_size(data)
+    }
+
+    /// Like [`PSF2Font::new`], but ignores the font's unicode table, for fonts only ever
+    /// looked up by glyph index. Lookups then work as for a font without a table: ASCII
+    /// and every other codepoint resolve to the glyph at their own index, so most
+    /// non-ASCII characters miss (or hit an unrelated glyph). Unlike the `ascii-only`
+    /// feature this is chosen per font at runtime.
+    pub const fn new_no_unicode(data: &'a [u8]) -> Result<Self, PsfError> {
+        match Self::new(data) {
+            Ok(mut font) => {
+                font.unicode_mapping = &[];
+                font.ascii_direct = true;
+                Ok(font)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Builds a font from a header and separately stored glyph bitmaps and unicode table,
+    /// e.g. for a kernel that loaded them into different pages. `unicode` is only used
+    /// if the header's flags say there is a table; pass `&[]` otherwise.
+    ///
+    /// `glyphs` must hold exactly `num_glyphs * bytes_per_glyph` bytes. The header's
+    /// magic and version aren't checked, and `header_size` only matters in that values
+    /// below 32 mark `unicode` as a PSF1 (UCS-2) table, as [`PSF2Font::new`] records
+    /// for PSF1 files.
+    pub const fn from_parts(
+        header: PSF2Header,
+        glyphs: &'a [u8],
+        unicode: &'a [u8],
+    ) -> Result<Self, PsfError> {
+        Self::assemble(header, glyphs, unicode)
+    }
+
+    /// Parses a font in a `const` context, e.g. to bake a bundled font's metrics in at compile time:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "s8x16")] {
+    /// use spleen_font::{PSF2Font, FONT_8X16};
+    ///
+    /// const FONT: PSF2Font = PSF2Font::new_const(FONT_8X16);
+    /// const CELL_WIDTH: u32 = FONT.width;
+    /// # assert_eq!(CELL_WIDTH, 8);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `data` is not a valid font. In a `const` item this is a compile error.
+    pub const fn new_const(data: &'a [u8]) -> Self {
+        match Self::new(data) {
+            Ok(font) => font,
+            Err(e) => panic!("{}", e.as_str()),
+        }
+    }
+}
+
+impl PSF2Font<'static> {
+    /// Parses a font embedded in the binary, such as the bundled ones, into a font that
+    /// can be stored in a `static`:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "s8x16")] {
+    /// use spleen_font::{PSF2Font, FONT_8X16};
+    ///
+    /// static FONT: PSF2Font<'static> = match PSF2Font::from_static(FONT_8X16) {
+    ///     Ok(font) => font,
+    ///     Err(_) => panic!("bad font"),
+    /// };
+    /// # assert_eq!(FONT.width, 8);
+    /// # }
+    /// ```
+    pub const fn from_static(blob: &'static [u8]) -> Result<Self, PsfError> {
+        Self::new(blob)
+    }
+}
+
+// Checked here so a field that isn't thread-safe can't silently make fonts `!Sync`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PSF2Font<'static>>();
+};
+
+impl<'a, const CACHE: usize> PSF2Font<'a, CACHE> {
+    /// Like [`PSF2Font::new`], with a glyph cache of `CACHE` entries instead of the default:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "s8x16")] {
+    /// use spleen_font::{PSF2Font, FONT_8X16};
+    ///
+    /// // No cache: every non-ASCII lookup scans the unicode table.
+    /// let font = PSF2Font::<0>::with_cache_size(FONT_8X16).unwrap();
+    /// # assert_eq!(font.width, 8);
+    /// # }
+    /// ```
+    pub const fn with_cache_size(data: &'a [u8]) -> Result<Self, PsfError> {
         let header = match PSF2Header::from_bytes(data) {
-            Ok(header) => header,
+            Ok(header) => {
+                // The bitmaps start at `header_size`, which may be larger than the
+                // 32 bytes we parse (reserved/extended headers), but never smaller.
+                if header.header_size < 32 || header.header_size as usize > data.len() {
+                    return Err(PsfError::InvalidHeaderSize);
+                }
+                header
+            }
+            // A PSF2 blob that fails to parse keeps its reason; anything else may be PSF1.
+            Err(e) if Self::has_psf2_magic(data) => return Err(e),
             Err(_) => {
-                if Self::is_psf1(data) {
-                    Self::psf1_to_header(data)?
-                } else {
-                    return Err("Unsupported format - Invalid PSF1 header");
+                if !Self::is_psf1(data) {
+                    return Err(PsfError::InvalidMagic);
+                }
+                match Self::psf1_to_header(data) {
+                    Ok(header) => header,
+                    Err(e) => return Err(e),
                 }
             }
         };
 
-        // Calculate offsets and ensure data is valid
+        // Calculate offsets and ensure data is valid. A crafted header can ask for more
+        // bytes than `usize` holds; no slice is that long, so that's too short as well.
         let glyphs_offset = header.header_size as usize;
-        let glyphs_size = header.num_glyphs as usize * header.bytes_per_glyph as usize;
-        let unicode_offset = glyphs_offset + glyphs_size;
+        let unicode_offset = (header.num_glyphs as usize)
+            .saturating_mul(header.bytes_per_glyph as usize)
+            .saturating_add(glyphs_offset);
+        if unicode_offset > data.len() {
+            return Err(PsfError::DataTooShort {
+                needed: unicode_offset,
+                got: data.len(),
+            });
+        }
 
-        if data.len() < unicode_offset {
-            return Err("PSF2 data too short");
+        // Extract glyph data and unicode mapping.
+        // Range indexing isn't available in `const fn`, so we split instead.
+        let (glyphs, tail) = data.split_at(unicode_offset);
+        let (_, glyphs) = glyphs.split_at(glyphs_offset);
+        Self::assemble(header, glyphs, tail)
+    }
+
+    /// [`PSF2Font::from_parts`], for any cache size.
+    const fn assemble(
+        header: PSF2Header,
+        glyphs: &'a [u8],
+        unicode: &'a [u8],
+    ) -> Result<Self, PsfError> {
+        // Zero-sized glyphs would make every row computation divide by zero or loop.
+        if header.width == 0 || header.height == 0 || header.bytes_per_glyph == 0 {
+            return Err(PsfError::InvalidDimensions);
+        }
+        // Glyphs shorter than their rows would be truncated; padding after them is fine.
+        if (header.bytes_per_glyph as u64) < header.width.div_ceil(8) as u64 * header.height as u64
+        {
+            return Err(PsfError::GlyphSizeMismatch);
+        }
+        if glyphs.len() as u64 != header.num_glyphs as u64 * header.bytes_per_glyph as u64 {
+            return Err(PsfError::GlyphSizeMismatch);
         }
 
-        // Extract glyph data and unicode mapping
-        let glyphs = &data[glyphs_offset..unicode_offset];
-        let unicode_mapping = if header.flags & 1 != 0 {
-            &data[unicode_offset..]
-        } else {
-            &[]
-        };
+        // With `ascii-only` the table is never consulted, so don't even keep a slice to it.
+        let has_table = header.flags & 1 != 0 && !cfg!(feature = "ascii-only");
+        let unicode_mapping: &[u8] = if has_table { unicode } else { &[] };
+        let psf1 = header.header_size < 32;
+        let unicode_mapping = Self::trim_table(unicode_mapping, header.num_glyphs, psf1);
+
+        // PSF1 tables hold UCS-2 entries, which we don't decode; legacy console
+        // fonts keep ASCII at its own index, so we trust the fast path for them.
+        let ascii_direct = psf1 || Self::ascii_is_identity(unicode_mapping);
+        let codepoint_span = Self::codepoint_span(unicode_mapping, psf1);
 
         Ok(Self {
             height: header.height,
@@ -94,27 +290,171 @@ impl<'a> PSF2Font<'a> {
             num_glyphs: header.num_glyphs,
             glyphs,
             unicode_mapping,
+            ascii_direct,
             cache: Cache::new(),
+            oob_policy: OobPolicy::None,
+            oob_fallback: None,
+            line_gap: 0,
+            cell_overlap: 0,
+            zero_width: COMBINING_MARKS,
+            index: &[],
+            fallback_candidates: &[REPLACEMENT_CHAR, FALLBACK_CHAR],
+            codepoint_span,
         })
     }
 
-    fn is_psf1(data: &[u8]) -> bool {
+    /// Cuts `table` after its `num_glyphs`-th record, dropping whatever follows, e.g. the
+    /// padding of a firmware region the font is embedded in. Records end in `0xFF`, or
+    /// `0xFFFF` for the UCS-2 tables of PSF1 fonts; a table with fewer records is kept whole.
+    const fn trim_table(table: &[u8], num_glyphs: u32, ucs2: bool) -> &[u8] {
+        let unit = if ucs2 { 2 } else { 1 };
+        let (mut p, mut records) = (0, 0);
+        while records < num_glyphs && p + unit <= table.len() {
+            if table[p] == 0xFF && (!ucs2 || table[p + 1] == 0xFF) {
+                records += 1;
+            }
+            p += unit;
+        }
+        if records < num_glyphs {
+            return table;
+        }
+        table.split_at(p).0
+    }
+
+    /// The lowest and highest single codepoint `table` maps (sequences don't count), so
+    /// scans can rule out codepoints outside them without reading the table.
+    const fn codepoint_span(table: &[u8], ucs2: bool) -> (u32, u32) {
+        let (mut lo, mut hi) = (u32::MAX, 0);
+        let mut p = 0;
+        let mut in_seq = false;
+        while p < table.len() {
+            let (cp, len) = if ucs2 {
+                if p + 1 >= table.len() {
+                    break;
+                }
+                match u16::from_le_bytes([table[p], table[p + 1]]) {
+                    0xFFFF => {
+                        in_seq = false;
+                        (None, 2)
+                    }
+                    0xFFFE => {
+                        in_seq = true;
+                        (None, 2)
+                    }
+                    0xD800..=0xDFFF => (None, 2),
+                    unit => (Some(unit as u32), 2),
+                }
+            } else {
+                match table[p] {
+                    0xFF => {
+                        in_seq = false;
+                        (None, 1)
+                    }
+                    0xFE => {
+                        in_seq = true;
+                        (None, 1)
+                    }
+                    b => {
+                        // Parsed as `UnicodeTableIter` does, which gives up at a bad byte.
+                        let Some(n) = utf8_seq_len(b) else {
+                            break;
+                        };
+                        if p + n > table.len() {
+                            break;
+                        }
+                        let mut cp = match n {
+                            1 => b as u32,
+                            2 => b as u32 & 0x1F,
+                            3 => b as u32 & 0x0F,
+                            _ => b as u32 & 0x07,
+                        };
+                        let mut i = 1;
+                        while i < n {
+                            cp = cp << 6 | (table[p + i] & 0x3F) as u32;
+                            i += 1;
+                        }
+                        (Some(cp), n)
+                    }
+                }
+            };
+            if let (Some(cp), false) = (cp, in_seq) {
+                if cp < lo {
+                    lo = cp;
+                }
+                if cp > hi {
+                    hi = cp;
+                }
+            }
+            p += len;
+        }
+        (lo, hi)
+    }
+
+    /// Checks that every printable ASCII character (0x20..=0x7E) is listed in the
+    /// unicode record of the glyph with the same index, i.e. the ASCII fast path
+    /// returns the same glyph a table scan would. Fonts without a table map
+    /// codepoints to indices by definition.
+    const fn ascii_is_identity(table: &[u8]) -> bool {
+        const FIRST_CHECKED: u32 = 0x20;
+        const RECORDS: u32 = 0x7F;
+
+        let mut p = 0;
+        let mut record = 0u32;
+        let mut found = false;
+        while p < table.len() && record < RECORDS {
+            match table[p] {
+                0xFF => {
+                    if record >= FIRST_CHECKED && !found {
+                        return false;
+                    }
+                    record += 1;
+                    found = false;
+                    p += 1;
+                }
+                // Sequences can't stand in for a single codepoint; skip to the end of the record.
+                0xFE => {
+                    while p < table.len() && table[p] != 0xFF {
+                        p += 1;
+                    }
+                }
+                // Multi-byte UTF-8 never contains bytes below 0x80, so a byte
+                // comparison is enough to spot a single ASCII codepoint.
+                b => {
+                    found |= b as u32 == record;
+                    p += 1;
+                }
+            }
+        }
+        table.is_empty() || record == RECORDS
+    }
+
+    const fn has_psf2_magic(data: &[u8]) -> bool {
+        data.len() >= 4
+            && data[0] == PSF2_MAGIC[0]
+            && data[1] == PSF2_MAGIC[1]
+            && data[2] == PSF2_MAGIC[2]
+            && data[3] == PSF2_MAGIC[3]
+    }
+
+    const fn is_psf1(data: &[u8]) -> bool {
         data.len() >= 4 && data[0] == 0x36 && data[1] == 0x04
     }
 
-    fn psf1_to_header(data: &[u8]) -> Result<PSF2Header, &'static str> {
+    const fn psf1_to_header(data: &[u8]) -> Result<PSF2Header, PsfError> {
         if !Self::is_psf1(data) {
-            return Err("bad PSF1 magic");
+            return Err(PsfError::InvalidMagic);
         }
-        let charsize = data[2] as u32;
-        let mode = data[3];
-        let num_glyphs = if mode & 0x01 != 0 { 512 } else { 256 };
+        // PSF1 header: magic (2 bytes), mode (1 byte), charsize (1 byte).
+        let mode = data[2];
+        let charsize = data[3] as u32;
+        let num_glyphs = if mode & PSF1_MODE512 != 0 { 512 } else { 256 };
+        let has_table = mode & (PSF1_MODEHASTAB | PSF1_MODEHASSEQ) != 0;
 
         Ok(PSF2Header {
             magic: PSF2_MAGIC, // dummy value so rest of code accepts it
             version: 0,
             header_size: 4,
-            flags: if mode & 0x02 != 0 { 1 } else { 0 }, // unicode flag
+            flags: if has_table { 1 } else { 0 }, // unicode flag
             num_glyphs,
             bytes_per_glyph: charsize,
             height: charsize,
@@ -126,118 +466,923 @@ impl<'a> PSF2Font<'a> {
     /// Returns a `Glyph` for a given UTF-8 byte slice.
     pub fn glyph_for_utf8(&mut self, text: &[u8]) -> Option<Glyph<'a>> {
         let slice = self.get_glyph_data(text)?;
-        Some(Glyph::new(slice, self.width as usize))
+        Some(self.glyph_from(slice))
+    }
+
+    /// Returns the `Glyph` for a single character.
+    pub fn glyph_for_char(&mut self, c: char) -> Option<Glyph<'a>> {
+        let mut buf = [0u8; 4];
+        self.glyph_for_utf8(c.encode_utf8(&mut buf).as_bytes())
+    }
+
+    /// Returns the `Glyph` for codepoint `cp`, for callers that decoded their text
+    /// themselves. Surrogates and values past `0x10FFFF` aren't characters and get `None`;
+    /// ASCII takes the same fast path as in [`get_glyph_data`](Self::get_glyph_data).
+    pub fn glyph_for_codepoint(&mut self, cp: u32) -> Option<Glyph<'a>> {
+        self.glyph_for_char(char::from_u32(cp)?)
+    }
+
+    /// Returns the `Glyph` for `s`, which must be exactly one `char`: empty and
+    /// multi-character strings are `None`, rather than quietly drawing their first
+    /// character, which for e.g. `"e\u{301}"` would drop the accent. Look sequences up
+    /// with [`glyph_for_utf8`](Self::glyph_for_utf8) instead.
+    pub fn glyph_str(&mut self, s: &str) -> Option<Glyph<'a>> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => self.glyph_for_char(c),
+            _ => None,
+        }
+    }
+
+    /// Returns glyph number `idx` as a `Glyph`, without going through any character
+    /// mapping, e.g. to dump a font's atlas or fetch glyphs by the indices
+    /// [`unicode_entries`](Self::unicode_entries) reports. Out-of-range indices follow
+    /// the [`OobPolicy`], like [`glyph_by_idx`](Self::glyph_by_idx).
+    pub fn glyph_at(&self, idx: u32) -> Option<Glyph<'a>> {
+        let slice = self.glyph_by_idx(idx)?;
+        Some(self.glyph_from(slice))
+    }
+
+    /// [`glyph_at`](Self::glyph_at) under the name renderers that map characters to
+    /// indices with their own tables tend to look for: the lookup skips the unicode table
+    /// and cache entirely.
+    #[inline]
+    pub fn glyph_by_index(&self, idx: u32) -> Option<Glyph<'a>> {
+        self.glyph_at(idx)
+    }
+
+    /// Wraps a glyph's bytes with this font's width and height.
+    pub(crate) fn glyph_from(&self, slice: &'a [u8]) -> Glyph<'a> {
+        Glyph::with_height(slice, self.width as usize, self.height as usize)
+    }
+
+    /// Returns the glyph for `c`, or if the font has none, for the first of the
+    /// [fallback candidates](Self::set_fallback_candidates) it has, or else glyph
+    /// [`notdef_index`](Self::notdef_index), so every character gets drawn as something.
+    /// Only `None` for a font without any glyphs.
+    pub fn glyph_or_replacement(&mut self, c: char) -> Option<Glyph<'a>> {
+        if let Some(glyph) = self.glyph_for_char(c) {
+            return Some(glyph);
+        }
+        let candidates = self.fallback_candidates;
+        candidates
+            .iter()
+            .find_map(|&c| self.glyph_for_char(c))
+            .or_else(|| self.glyph_at(self.notdef_index()))
+    }
+
+    /// Sets the characters [`glyph_or_replacement`](Self::glyph_or_replacement) tries, in
+    /// order, for characters the font lacks, e.g. `&['\u{2022}', '*', '.']` for a bullet.
+    /// By default that's [`REPLACEMENT_CHAR`], then [`FALLBACK_CHAR`].
+    pub fn set_fallback_candidates(&mut self, chars: &'static [char]) {
+        self.fallback_candidates = chars;
+    }
+
+    /// Iterates the glyphs of `text`, one item per `char` (`None` where the font has no glyph),
+    /// so positions stay aligned with the characters.
+    ///
+    /// The iterator is double-ended: `font.glyphs(text).rev()` walks from the last character,
+    /// e.g. to place right-aligned text from the right edge without collecting first.
+    pub fn glyphs<'f>(&'f mut self, text: &'f str) -> Glyphs<'f, 'a, CACHE> {
+        Glyphs {
+            font: self,
+            chars: text.chars(),
+        }
+    }
+
+    /// Rasterizes `text` as one line of cells into the bit-packed bitmap `out`, e.g. an
+    /// offscreen buffer for DMA. Rows are `out_stride_bits` apart, pixels are MSB-first
+    /// like the font's own bitmaps, and character `i` lands at x = `i * cell_advance`
+    /// (see [`set_cell_overlap`](Self::set_cell_overlap); overlapping columns are OR-ed).
+    ///
+    /// Every bit inside the written `(width_px, height_px)` area is overwritten, so `out`
+    /// needs no clearing first; characters the font has no glyph for are left blank.
+    /// Bits outside that area are untouched.
+    ///
+    /// Returns `PsfError::BufferTooSmall` if the line is wider than `out_stride_bits` or
+    /// `out` can't hold `height` rows of that stride; nothing is written in that case.
+    pub fn rasterize_line(
+        &mut self,
+        text: &str,
+        out: &mut [u8],
+        out_stride_bits: usize,
+    ) -> Result<(usize, usize), PsfError> {
+        let (width, advance) = (self.width as usize, self.cell_advance());
+        let height = self.height as usize;
+        let width_px = self.cells_width(text.chars().count());
+        let rows_bits = height
+            .saturating_sub(1)
+            .checked_mul(out_stride_bits)
+            .and_then(|bits| bits.checked_add(width_px));
+        // A stride so large the area overflows can't fit in any buffer either.
+        let fits = rows_bits.is_some_and(|bits| bits.div_ceil(8) <= out.len());
+        if width_px > out_stride_bits || !fits {
+            return Err(PsfError::BufferTooSmall);
+        }
+
+        for (cell, c) in text.chars().enumerate() {
+            let glyph = self.glyph_for_char(c);
+            for y in 0..height {
+                let row = glyph.and_then(|g| g.row(y));
+                let start = y * out_stride_bits + cell * advance;
+                for x in 0..width {
+                    let on = row.is_some_and(|r| r.bit(x));
+                    let (byte, mask) = ((start + x) / 8, 0x80 >> ((start + x) % 8));
+                    if on {
+                        out[byte] |= mask;
+                    } else if cell == 0 || x >= self.cell_overlap {
+                        // Columns shared with the previous cell were written by it.
+                        out[byte] &= !mask;
+                    }
+                }
+            }
+        }
+        Ok((width_px, height))
     }
 
     /// Returns glyph data for a given UTF-8 byte slice.
-    /// Goes through three paths:
-    /// 1. If the text is a single ASCII character:
+    /// Goes through four paths:
+    /// 1. If the text is a single ASCII character and the font keeps ASCII at its own index:
     ///    we simply return the glyph index as mapped to the UTF-8 index.
-    /// 2. If the we get a cache hit for our sequence, we return the cached glyph data.
-    /// 3. If all else fails, we do a linear search through our unicode mapping table.
+    /// 2. If the font has no unicode table, the glyph index *is* the codepoint,
+    ///    so we decode the scalar and index directly.
+    /// 3. If the we get a cache hit for our sequence, we slice the glyph data at the cached offset.
+    /// 4. If all else fails, we do a linear search through our unicode mapping table.
+    ///
+    /// Input that isn't complete, well-formed UTF-8 (truncated, overlong, surrogates) is `None`.
+    ///
+    /// With the `ascii-only` feature only the first path exists; anything else is `None`.
+    ///
+    /// [`try_glyph`](Self::try_glyph) tells why a lookup missed.
     pub fn get_glyph_data(&mut self, text: &[u8]) -> Option<&'a [u8]> {
-        if text.len() == 1 && text[0] <= 0x7F {
-            return self.glyph_by_idx(text[0] as u32);
+        self.lookup(text).ok()
+    }
+
+    /// Like [`glyph_for_utf8`](Self::glyph_for_utf8), but says why there's no glyph,
+    /// e.g. to track down holes in rendered text.
+    ///
+    /// [`LookupMiss::IndexOutOfRange`] only happens under [`OobPolicy::None`]; the other
+    /// policies substitute a glyph instead.
+    pub fn try_glyph(&mut self, text: &[u8]) -> Result<Glyph<'a>, LookupMiss> {
+        let slice = self.lookup(text)?;
+        Ok(self.glyph_from(slice))
+    }
+
+    /// The lookup behind [`get_glyph_data`](Self::get_glyph_data) and
+    /// [`try_glyph`](Self::try_glyph).
+    fn lookup(&mut self, text: &[u8]) -> Result<&'a [u8], LookupMiss> {
+        let malformed = || {
+            if is_complete_utf8(text) {
+                LookupMiss::NotMapped
+            } else {
+                LookupMiss::MalformedUtf8
+            }
+        };
+
+        let idx = if self.is_direct(text) {
+            self.direct_index(text).ok_or_else(malformed)?
+        } else {
+            // Partial or malformed input could otherwise match a prefix of some table entry.
+            if !is_complete_utf8(text) {
+                return Err(LookupMiss::MalformedUtf8);
+            }
+            if let Some(offset) = self.cache.get(text) {
+                return self.glyph_at_offset(offset).ok_or(LookupMiss::NotMapped);
+            }
+            let idx = self.scan_unicode_table(text).ok_or(LookupMiss::NotMapped)?;
+            if let Some(offset) = self.glyph_offset(idx) {
+                self.cache.insert(text, offset);
+            }
+            idx
+        };
+        self.glyph_by_idx(idx)
+            .ok_or(LookupMiss::IndexOutOfRange(idx))
+    }
+
+    /// The glyph drawn for characters a font has no glyph for, `.notdef` by convention:
+    /// always glyph 0.
+    pub fn notdef_index(&self) -> u32 {
+        0
+    }
+
+    /// The index of the glyph for `c`, or [`notdef_index`](Self::notdef_index) if the font
+    /// has none (or maps it past the last glyph), so renderers get an index for every
+    /// character. Goes through the cache like [`glyph_for_char`](Self::glyph_for_char).
+    pub fn index_of_char(&mut self, c: char) -> u32 {
+        let mut buf = [0u8; 4];
+        let key = c.encode_utf8(&mut buf).as_bytes();
+        let idx = if self.is_direct(key) {
+            self.direct_index(key)
+        } else if let Some(offset) = self.cache.get(key) {
+            Some(offset / self.bytes_per_glyph)
+        } else {
+            let idx = self.scan_unicode_table(key);
+            if let Some(offset) = idx.and_then(|i| self.glyph_offset(i)) {
+                self.cache.insert(key, offset);
+            }
+            idx
+        };
+        idx.filter(|&i| i < self.num_glyphs)
+            .unwrap_or(self.notdef_index())
+    }
+
+    /// Resolves every character of `text` into the glyph cache ahead of time, so the
+    /// first real render of it doesn't pay for table scans, e.g. for a status bar's
+    /// fixed labels at startup. Characters that resolve directly (ASCII, fonts without
+    /// a table) are skipped, as are ones already cached.
+    ///
+    /// The cache holds `CACHE` entries and evicts round-robin, so priming more distinct
+    /// characters than that keeps only the most recent ones. Priming isn't counted in
+    /// [`cache_stats`](Self::cache_stats).
+    pub fn prime_cache(&mut self, text: &str) {
+        let mut buf = [0u8; 4];
+        for c in text.chars() {
+            let key = c.encode_utf8(&mut buf).as_bytes();
+            if self.is_direct(key) || self.cache.peek(key).is_some() {
+                continue;
+            }
+            if let Some(offset) = self
+                .scan_unicode_table(key)
+                .and_then(|i| self.glyph_offset(i))
+            {
+                self.cache.insert(key, offset);
+            }
         }
+    }
+
+    /// Hit/miss counters of the glyph cache since the font was parsed.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
 
-        if let Some(idx) = self.cache.get(text) {
-            return self.glyph_by_idx(idx);
+    /// Bytes of RAM this font takes up itself, mostly its `CACHE`-entry glyph cache; the
+    /// borrowed font data (and any [`build_index`](Self::build_index) scratch) isn't counted.
+    pub const fn ram_footprint(&self) -> usize {
+        // The cache is stored inline, so the struct's size covers it.
+        core::mem::size_of::<Self>()
+    }
+
+    /// Whether `text` resolves without the unicode table (paths 1 and 2 above),
+    /// in which case there is nothing worth caching.
+    pub(crate) fn is_direct(&self, text: &[u8]) -> bool {
+        (self.ascii_direct && text.len() == 1 && text[0] <= 0x7F)
+            || cfg!(feature = "ascii-only")
+            || self.unicode_mapping.is_empty()
+    }
+
+    /// Resolves a lookup for which [`is_direct`](Self::is_direct) holds.
+    pub(crate) fn direct_index(&self, text: &[u8]) -> Option<u32> {
+        if self.ascii_direct && text.len() == 1 && text[0] <= 0x7F {
+            return Some(text[0] as u32);
         }
 
-        if let Some(idx) = self.scan_unicode_table(self.unicode_mapping, text) {
-            self.cache.insert(text, idx);
-            return self.glyph_by_idx(idx);
+        if cfg!(feature = "ascii-only") {
+            return None;
         }
 
-        None
+        decode_utf8(text)
     }
 
     /// Scans the unicode mapping table for a given sequence of bytes.
     /// Returns the glyph index if found, otherwise None.
     /// Does perform a O(n) search through the table. We add a cache to make this less expensive.
-    fn scan_unicode_table(&mut self, table: &[u8], sequence: &[u8]) -> Option<u32> {
-        let mut glyph_idx: u32 = 0;
-        let mut p: usize = 0;
+    /// With an index from [`build_index`](Self::build_index), single codepoints are a binary search.
+    pub(crate) fn scan_unicode_table(&self, sequence: &[u8]) -> Option<u32> {
+        // A codepoint outside the table's span can't match any entry; a common miss.
+        let (lo, hi) = self.codepoint_span;
+        if decode_utf8(sequence).is_some_and(|cp| cp < lo || cp > hi) {
+            return None;
+        }
 
-        const START_SEQ: u8 = 0xFE;
-        const END_REC: u8 = 0xFF; // Note that the end of **table** marker is 0xFF 0xFF.
+        if let (false, Some(cp)) = (self.index.is_empty(), decode_utf8(sequence)) {
+            // The first entry for `cp` has the lowest glyph, i.e. the one a scan finds first.
+            let first = self.index.partition_point(|&(c, _)| c < cp);
+            return match self.index.get(first) {
+                Some(&(c, glyph)) if c == cp => Some(glyph),
+                _ => None,
+            };
+        }
 
-        while p < table.len() {
-            if table.get(p..p + 2) == Some(&[END_REC, END_REC]) {
-                return None;
-            }
+        #[cfg(test)]
+        TABLE_SCANS.with(|n| n.set(n.get() + 1));
 
-            loop {
-                match table[p] {
-                    START_SEQ => p += 1,
-                    END_REC => {
-                        glyph_idx += 1;
-                        p += 1;
-                        break;
-                    }
-                    b => {
-                        let start = p;
-                        let n = match Self::next_utf8_len(b) {
-                            Some(n) => n,
-                            None => return None, // malformed UTF-8 -> no match
-                        };
-                        p += n;
-                        while p < table.len() && !matches!(table[p], START_SEQ | END_REC) {
-                            let n = match Self::next_utf8_len(table[p]) {
-                                Some(n) => n,
-                                None => return None,
-                            };
-                            p += n;
-                        }
+        // PSF1 sequences aren't supported (Spleen's PSF1 table has none), only single codepoints.
+        if self.is_psf1_font() {
+            let cp = decode_utf8(sequence)?;
+            return self
+                .single_codepoints()
+                .find(|&(_, c)| c == cp)
+                .map(|(glyph, _)| glyph);
+        }
 
-                        if &table[start..p] == sequence {
-                            return Some(glyph_idx);
-                        }
-                    }
-                }
-            }
+        // One scalar only matches single-codepoint entries, never a one-element sequence.
+        let single = decode_utf8(sequence).is_some();
+        self.classified_entries()
+            .find(|&(_, entry, in_seq)| entry == sequence && in_seq != single)
+            .map(|(glyph, _, _)| glyph)
+    }
+
+    /// [`unicode_entries`](Self::unicode_entries), each tagged with whether it is a
+    /// sequence (`0xFE` group) rather than a single codepoint.
+    fn classified_entries(&self) -> impl Iterator<Item = (u32, &'a [u8], bool)> + Clone + 'a {
+        let mut entries = self.unicode_entries();
+        core::iter::from_fn(move || {
+            let (glyph, entry) = entries.next()?;
+            Some((glyph, entry, entries.in_sequence()))
+        })
+    }
+
+    /// Parses every single-codepoint entry of the unicode table into `scratch`, sorted, and
+    /// from then on looks single codepoints up by binary search instead of scanning the
+    /// table, for targets that can spare the RAM (8 bytes per entry) for a one-time setup.
+    /// Sequences still go through the scan.
+    ///
+    /// Returns the number of entries the table has. If that's more than `scratch.len()`,
+    /// nothing is indexed (a partial index would miss characters); retry with a buffer of
+    /// that size; passing `&mut []` just counts.
+    pub fn build_index(&mut self, scratch: &'a mut [(u32, u32)]) -> usize {
+        let entries = self.single_codepoints();
+        let needed = entries.clone().count();
+        if needed > scratch.len() || self.unicode_mapping.is_empty() {
+            return needed;
         }
-        None
+
+        let index = &mut scratch[..needed];
+        for (slot, (glyph, cp)) in index.iter_mut().zip(entries) {
+            *slot = (cp, glyph);
+        }
+        index.sort_unstable();
+        self.index = index;
+        needed
+    }
+
+    /// Whether lookups go through a unicode table. Without one (no table in the font,
+    /// [`new_no_unicode`](PSF2Font::new_no_unicode) or the `ascii-only` feature), every
+    /// codepoint resolves to the glyph at its own index, so most non-ASCII text misses.
+    pub fn has_unicode_table(&self) -> bool {
+        !self.unicode_mapping.is_empty()
+    }
+
+    /// The raw unicode table following the glyph bitmaps, empty if the font has none
+    /// (or with the `ascii-only` feature). For PSF1 fonts this holds UCS-2 entries.
+    /// It ends with the record of the last glyph: bytes after that aren't part of it.
+    pub fn unicode_table(&self) -> &'a [u8] {
+        self.unicode_mapping
+    }
+
+    /// Parses the unicode table into `(glyph_index, entry)` pairs; see [`UnicodeTableIter`].
+    /// PSF1 tables aren't UTF-8, so this yields nothing for them.
+    pub fn unicode_entries(&self) -> UnicodeTableIter<'a> {
+        let table = if self.is_psf1_font() {
+            &[]
+        } else {
+            self.unicode_mapping
+        };
+        UnicodeTableIter::new(table, self.num_glyphs)
     }
 
-    /// Decode exactly one valid UTF-8 scalar and return (len, first_byte_masked)
-    /// Returns None on malformed UTF-8 or truncated input.
+    /// Counts the single-codepoint entries of the unicode table, i.e. how many characters
+    /// the font can draw; sequences (`0xFE` groups) are left out. As one glyph often
+    /// stands for several codepoints (`-` and `−`, say) this is usually above `num_glyphs`.
     ///
-    /// Every UTF-8 sequence starts with a leading byte that indicates the number of bytes in the sequence.
-    /// The leading byte is followed by continuation bytes that each start with the bits 10xxxxxx.
+    /// A codepoint listed under two glyphs counts twice; Spleen's tables have no such
+    /// duplicates. Fonts without a table map each glyph to one codepoint, so `num_glyphs`.
+    pub fn coverage(&self) -> usize {
+        if self.unicode_mapping.is_empty() {
+            return self.num_glyphs as usize;
+        }
+        self.single_codepoints().count()
+    }
+
+    /// Counts the glyphs with at least one set pixel, e.g. to skip the blank cells of a
+    /// font padded out to 256 or 512 glyphs when packing a sprite sheet. The space glyph
+    /// is blank too, so it isn't counted either.
+    pub fn non_blank_glyph_count(&self) -> u32 {
+        debug_assert!(self.bytes_per_glyph != 0, "new rejects zero-sized glyphs");
+        let blank = |data| self.glyph_from(data).set_pixel_count() == 0;
+        self.glyphs
+            .chunks_exact(self.bytes_per_glyph as usize)
+            .take(self.num_glyphs as usize)
+            .filter(|data| !blank(data))
+            .count() as u32
+    }
+
+    /// `(glyph_index, codepoint)` for every single-codepoint entry, from either table format.
+    fn single_codepoints(&self) -> impl Iterator<Item = (u32, u32)> + Clone + 'a {
+        let ucs2 = if self.is_psf1_font() {
+            self.unicode_mapping
+        } else {
+            &[]
+        };
+        self.classified_entries()
+            .filter(|&(_, _, in_seq)| !in_seq)
+            .filter_map(|(glyph, entry, _)| Some((glyph, decode_utf8(entry)?)))
+            .chain(Ucs2Codepoints::new(ucs2, self.num_glyphs))
+    }
+
+    /// PSF1 fonts are parsed into a header with the 4-byte PSF1 header size.
+    fn is_psf1_font(&self) -> bool {
+        self.header_size < HEADER_SIZE as u32
+    }
+
+    /// A stable 64-bit FNV-1a hash of the font's metrics and glyph bitmaps, e.g. to check
+    /// that a pre-baked atlas was built from the same font as the one loaded at runtime.
     ///
-    /// one byte:       0.......
-    /// two bytes:      110..... 10......
-    /// three bytes:    1110.... 10...... 10......
-    /// four bytes:     11110... 10...... 10...... 10......
+    /// Covers `width`, `height`, `num_glyphs`, `bytes_per_glyph` and the glyph data. The
+    /// unicode table is left out, so the value doesn't depend on the `ascii-only` feature.
+    pub fn fingerprint(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let metrics = [
+            self.width,
+            self.height,
+            self.num_glyphs,
+            self.bytes_per_glyph,
+        ];
+        metrics
+            .iter()
+            .flat_map(|field| field.to_le_bytes())
+            .chain(self.glyphs.iter().copied())
+            .fold(OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(PRIME)
+            })
+    }
+
+    /// Where the glyph bitmaps start in the parsed blob: the header size, also for PSF1
+    /// fonts. Glyph `i` is the `bytes_per_glyph` bytes at `glyphs_offset() + i *
+    /// bytes_per_glyph`, e.g. to patch it in a mutable copy of the blob. For fonts built
+    /// with [`from_parts`](PSF2Font::from_parts) this is where a blob with that header
+    /// would have them.
+    pub fn glyphs_offset(&self) -> usize {
+        self.header_size as usize
+    }
+
+    /// Where the unicode table starts in the parsed blob, right after the last glyph; see
+    /// [`glyphs_offset`](Self::glyphs_offset). Fonts without a table end here.
+    pub fn unicode_offset(&self) -> usize {
+        self.glyphs_offset() + self.glyphs.len()
+    }
+
+    /// Number of bytes per glyph row: `ceil(width / 8)`.
     #[inline]
-    fn next_utf8_len(b: u8) -> Option<usize> {
-        Some(match b {
-            0x00..=0x7F => 1, // 0xxxxxxx
-            0xC2..=0xDF => 2, // 110xxxxx
-            0xE0..=0xEF => 3, // 1110xxxx
-            0xF0..=0xF4 => 4, // 11110xxx
-            _ => return None, // continuation or invalid
-        })
+    pub fn bytes_per_row(&self) -> usize {
+        (self.width as usize).div_ceil(8)
+    }
+
+    /// Whether every glyph row fits in one byte (`width <= 8`), as in the 5×8, 6×12 and
+    /// 8×16 faces, so blitters can take a path built on [`Glyph::fast_row_u8`].
+    #[inline]
+    pub fn is_byte_wide(&self) -> bool {
+        self.width <= 8
     }
 
     /// Returns glyph data for a given glyph index.
-    /// If the index is out of bounds, returns None.
+    /// If the index is out of bounds, the [`OobPolicy`] decides; by default that's None.
     #[inline]
     pub fn glyph_by_idx(&self, idx: u32) -> Option<&'a [u8]> {
+        #[cfg(test)]
+        INDEX_COMPUTATIONS.with(|n| n.set(n.get() + 1));
+
+        let idx = if idx < self.num_glyphs {
+            idx
+        } else {
+            self.oob_fallback?
+        };
+
+        // In `usize`: the product can exceed `u32` for fonts with large glyphs.
+        let bytes_per_glyph = self.bytes_per_glyph as usize;
+        let off = (idx as usize).checked_mul(bytes_per_glyph)?;
+        let glyph = self.glyphs.get(off..off.checked_add(bytes_per_glyph)?);
+        // The glyph data was checked to hold exactly `num_glyphs` glyphs when parsing.
+        debug_assert!(
+            glyph.is_some_and(|g| g.len() == bytes_per_glyph),
+            "glyph {idx} is outside the glyph data"
+        );
+        glyph
+    }
+
+    /// Byte offset of glyph `idx` in the glyph data, for the cache. Out-of-range indices
+    /// have none: what they resolve to depends on the [`OobPolicy`], which may change
+    /// after they were cached. Offsets past `u32::MAX` aren't cached either.
+    pub(crate) fn glyph_offset(&self, idx: u32) -> Option<u32> {
         if idx >= self.num_glyphs {
             return None;
         }
+        idx.checked_mul(self.bytes_per_glyph)
+    }
+
+    /// The glyph whose bitmap starts at `offset`, as returned by
+    /// [`glyph_offset`](Self::glyph_offset); a cache hit skips re-indexing this way.
+    #[inline]
+    pub(crate) fn glyph_at_offset(&self, offset: u32) -> Option<&'a [u8]> {
+        let off = offset as usize;
+        self.glyphs
+            .get(off..off.checked_add(self.bytes_per_glyph as usize)?)
+    }
+
+    /// Switches to the font in `data` in place, keeping the options set on this one: the
+    /// [`OobPolicy`], line gap, cell overlap, zero-width ranges and fallback candidates. The glyph cache (with its
+    /// [`cache_stats`](Self::cache_stats)) and any [`build_index`](Self::build_index)
+    /// index are dropped, since glyph indices differ between fonts.
+    ///
+    /// If `data` doesn't parse, the error is returned and the current font is kept.
+    pub fn reload(&mut self, data: &'a [u8]) -> Result<(), PsfError> {
+        let mut font = Self::with_cache_size(data)?;
+        font.line_gap = self.line_gap;
+        font.set_cell_overlap(self.cell_overlap);
+        font.zero_width = self.zero_width;
+        font.fallback_candidates = self.fallback_candidates;
+        // Re-resolved, as the fallback glyph is an index into the new font.
+        font.set_oob_policy(self.oob_policy);
+        *self = font;
+        Ok(())
+    }
+
+    /// Sets what out-of-range glyph indices resolve to; see [`OobPolicy`].
+    pub fn set_oob_policy(&mut self, policy: OobPolicy) {
+        self.oob_policy = policy;
+        self.oob_fallback = match policy {
+            OobPolicy::None => None,
+            OobPolicy::Clamp => self.num_glyphs.checked_sub(1),
+            OobPolicy::Replacement => [REPLACEMENT_CHAR, FALLBACK_CHAR].iter().find_map(|&c| {
+                let mut buf = [0u8; 4];
+                self.resolve_uncached(c.encode_utf8(&mut buf).as_bytes())
+                    .filter(|&idx| idx < self.num_glyphs)
+            }),
+        };
+    }
+
+    /// Sets the blank pixels the [layout](crate::layout) functions leave between lines,
+    /// e.g. 1–2 px for a more readable log view. Glyph bitmaps are unaffected.
+    pub fn set_line_gap(&mut self, px: usize) {
+        self.line_gap = px;
+    }
+
+    /// The line gap set by [`set_line_gap`](Self::set_line_gap); `0` by default.
+    pub fn line_gap(&self) -> usize {
+        self.line_gap
+    }
+
+    /// Distance in pixels from one line's top to the next: `height` plus the line gap.
+    pub fn line_height(&self) -> usize {
+        self.height as usize + self.line_gap
+    }
+
+    /// Makes adjacent cells overlap by `px` pixels in the [layout](crate::layout)
+    /// functions, so the advance is `width - px`, capped to leave at least one pixel. This
+    /// is for box-drawing and block characters whose lines should run into each other at
+    /// sizes where they'd otherwise leave a gap, not for typographic kerning: every cell
+    /// overlaps, whatever its characters.
+    pub fn set_cell_overlap(&mut self, px: usize) {
+        self.cell_overlap = px.min(self.width as usize - 1);
+    }
+
+    /// The overlap set by [`set_cell_overlap`](Self::set_cell_overlap); `0` by default.
+    pub fn cell_overlap(&self) -> usize {
+        self.cell_overlap
+    }
+
+    /// Distance in pixels from one cell's left edge to the next: `width` minus the cell
+    /// overlap.
+    pub fn cell_advance(&self) -> usize {
+        self.width as usize - self.cell_overlap
+    }
+
+    /// How far the layout functions move right after `c`: `0` for zero-width characters
+    /// such as combining accents, which are drawn over the previous cell, otherwise
+    /// [`cell_advance`](Self::cell_advance).
+    pub fn advance_of(&self, c: char) -> usize {
+        if self.zero_width.iter().any(|range| range.contains(&c)) {
+            0
+        } else {
+            self.cell_advance()
+        }
+    }
+
+    /// Replaces the codepoints [`advance_of`](Self::advance_of) treats as zero-width;
+    /// by default those are [`COMBINING_MARKS`]. Pass `&[]` to give every character a cell.
+    pub fn set_zero_width(&mut self, ranges: &'a [RangeInclusive<char>]) {
+        self.zero_width = ranges;
+    }
+
+    /// The ranges set by [`set_zero_width`](Self::set_zero_width).
+    pub(crate) fn zero_width_ranges(&self) -> &'a [RangeInclusive<char>] {
+        self.zero_width
+    }
+
+    /// The current [`OobPolicy`].
+    pub fn oob_policy(&self) -> OobPolicy {
+        self.oob_policy
+    }
+
+    /// Resolves `text` to a glyph index (in range or not) the way
+    /// [`get_glyph_data`](Self::get_glyph_data) does, without consulting or filling the cache.
+    fn resolve_uncached(&self, text: &[u8]) -> Option<u32> {
+        if self.is_direct(text) {
+            self.direct_index(text)
+        } else if is_complete_utf8(text) {
+            self.scan_unicode_table(text)
+        } else {
+            None
+        }
+    }
+
+    /// Writes a reduced PSF2 font holding only the glyphs for `chars` into `out`,
+    /// and returns the number of bytes written.
+    ///
+    /// Glyph 0 is always kept, at index 0, as the fallback glyph. The other glyphs keep
+    /// their original relative order, and the new unicode table maps exactly the requested
+    /// characters. Characters the font has no glyph for are left out.
+    pub fn subset(&self, chars: &[char], out: &mut [u8]) -> Result<usize, PsfError> {
+        // First pass: size the output.
+        let mut kept = 0;
+        let mut table_len = 0;
+        let mut last = 0;
+        self.for_each_mapping(chars, |glyph, c| {
+            if glyph != last {
+                kept += 1;
+                last = glyph;
+            }
+            table_len += c.len_utf8();
+        });
+
+        let bytes_per_glyph = self.bytes_per_glyph as usize;
+        let num_glyphs = kept + 1;
+        let glyphs_len = num_glyphs * bytes_per_glyph;
+        let len = HEADER_SIZE + glyphs_len + table_len + num_glyphs;
+        let out = out.get_mut(..len).ok_or(PsfError::BufferTooSmall)?;
+
+        // Second pass: emit the glyphs and their records in the same order.
+        let (header, rest) = out.split_at_mut(HEADER_SIZE);
+        write_header(
+            header,
+            1,
+            num_glyphs as u32,
+            self.bytes_per_glyph,
+            self.width,
+            self.height,
+        );
+        let (bitmaps, table) = rest.split_at_mut(glyphs_len);
+        let mut slots = bitmaps.chunks_exact_mut(bytes_per_glyph);
+        let mut copy_glyph = |idx: u32| {
+            if let (Some(slot), Some(data)) = (slots.next(), self.glyph_by_idx(idx)) {
+                slot.copy_from_slice(data);
+            }
+        };
+
+        copy_glyph(0);
+        let mut p = 0;
+        let mut last = 0;
+        self.for_each_mapping(chars, |glyph, c| {
+            if glyph != last {
+                table[p] = END_REC;
+                p += 1;
+                copy_glyph(glyph);
+                last = glyph;
+            }
+            p += c.encode_utf8(&mut table[p..]).len();
+        });
+        table[p] = END_REC;
+
+        Ok(len)
+    }
+
+    /// Calls `f(glyph, c)` for every distinct char in `chars` that maps to `glyph`,
+    /// in ascending glyph order.
+    fn for_each_mapping(&self, chars: &[char], mut f: impl FnMut(u32, char)) {
+        let distinct = || {
+            chars
+                .iter()
+                .enumerate()
+                .filter(|&(i, c)| !chars[..i].contains(c))
+                .map(|(_, &c)| c)
+        };
+
+        if self.unicode_mapping.is_empty() {
+            for glyph in 0..self.num_glyphs {
+                distinct()
+                    .filter(|&c| c as u32 == glyph)
+                    .for_each(|c| f(glyph, c));
+            }
+            return;
+        }
+
+        for (glyph, cp) in self.single_codepoints() {
+            distinct()
+                .filter(|&c| c as u32 == cp)
+                .for_each(|c| f(glyph, c));
+        }
+    }
+}
+
+#[cfg(test)]
+std::thread_local! {
+    /// Calls to [`PSF2Font::glyph_by_idx`] on this thread, to check which lookups re-index.
+    pub(crate) static INDEX_COMPUTATIONS: core::cell::Cell<u32> = const { core::cell::Cell::new(0) };
+    /// Linear scans of a unicode table on this thread, to check which lookups skip them.
+    pub(crate) static TABLE_SCANS: core::cell::Cell<u32> = const { core::cell::Cell::new(0) };
+}
+
+impl<const CACHE: usize> Clone for PSF2Font<'_, CACHE> {
+    /// A second handle to the same font, e.g. one per subsystem so their caches don't
+    /// compete. Clones share the borrowed font data (and any
+    /// [`build_index`](Self::build_index) index) and keep this font's options, but start
+    /// with an empty cache and zeroed [`cache_stats`](Self::cache_stats).
+    fn clone(&self) -> Self {
+        PSF2Font {
+            cache: Cache::new(),
+            ..*self
+        }
+    }
+}
+
+/// A one-line summary for boot logs, e.g. `PSF2 12x24, 1344 glyphs, unicode table present`.
+///
+/// The table is reported as absent if the font has none, or with the `ascii-only`
+/// feature, which drops it.
+impl<const CACHE: usize> core::fmt::Display for PSF2Font<'_, CACHE> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let format = if self.is_psf1_font() { "PSF1" } else { "PSF2" };
+        let table = if self.unicode_mapping.is_empty() {
+            "no unicode table"
+        } else {
+            "unicode table present"
+        };
+        write!(
+            f,
+            "{format} {}x{}, {} glyphs, {table}",
+            self.width, self.height, self.num_glyphs
+        )
+    }
+}
+
+/// Iterator over the glyphs of a string; see [`PSF2Font::glyphs`].
+pub struct Glyphs<'f, 'a, const CACHE: usize = DEFAULT_CACHE_SIZE> {
+    font: &'f mut PSF2Font<'a, CACHE>,
+    chars: core::str::Chars<'f>,
+}
+
+impl<'a, const CACHE: usize> Iterator for Glyphs<'_, 'a, CACHE> {
+    type Item = Option<Glyph<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.chars.next()?;
+        Some(self.font.glyph_for_char(c))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chars.size_hint()
+    }
+}
+
+impl<const CACHE: usize> DoubleEndedIterator for Glyphs<'_, '_, CACHE> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let c = self.chars.next_back()?;
+        Some(self.font.glyph_for_char(c))
+    }
+}
+
+/// Walks the records of a PSF2 unicode table, yielding `(glyph_index, entry)` for each
+/// single codepoint or `0xFE`-prefixed sequence (without the marker), as UTF-8 bytes.
+///
+/// Records are counted rather than trusting `0xFF 0xFF` as an end marker, since that is
+/// also just an unmapped glyph's empty record. Iteration stops after `num_glyphs`
+/// records, at the end of the table, or at the first malformed UTF-8 byte.
+///
+/// ```rust
+/// # #[cfg(all(feature = "s5x8", not(feature = "ascii-only")))] {
+/// use spleen_font::{PSF2Font, FONT_5X8};
+///
+/// let font = PSF2Font::new(FONT_5X8).unwrap();
+/// let (glyph, _) = font.unicode_entries().find(|&(_, e)| e == "é".as_bytes()).unwrap();
+/// # assert!(glyph < font.num_glyphs);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct UnicodeTableIter<'a> {
+    table: &'a [u8],
+    p: usize,
+    glyph: u32,
+    num_glyphs: u32,
+    in_seq: bool,
+}
+
+impl<'a> UnicodeTableIter<'a> {
+    /// Parses `table`, the bytes following the glyph bitmaps of a PSF2 font with `num_glyphs` glyphs.
+    /// [`PSF2Font::unicode_entries`] does this for a parsed font.
+    pub fn new(table: &'a [u8], num_glyphs: u32) -> Self {
+        UnicodeTableIter {
+            table,
+            p: 0,
+            glyph: 0,
+            num_glyphs,
+            in_seq: false,
+        }
+    }
+
+    /// How many records (glyphs) have been fully read so far. Once iteration ends this
+    /// equals `num_glyphs` for a complete table, and is smaller if it was cut short.
+    pub fn records_read(&self) -> u32 {
+        self.glyph
+    }
+
+    /// Whether the entry last returned belongs to a `0xFE` sequence group rather than
+    /// being a single codepoint. A group may hold just one codepoint, so an entry's
+    /// length alone doesn't tell.
+    pub fn in_sequence(&self) -> bool {
+        self.in_seq
+    }
+}
+
+impl<'a> Iterator for UnicodeTableIter<'a> {
+    type Item = (u32, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        const START_SEQ: u8 = 0xFE;
+
+        while self.glyph < self.num_glyphs {
+            match *self.table.get(self.p)? {
+                END_REC => {
+                    self.glyph += 1;
+                    self.in_seq = false;
+                    self.p += 1;
+                }
+                START_SEQ => {
+                    self.in_seq = true;
+                    self.p += 1;
+                }
+                b => {
+                    let start = self.p;
+                    let Some(n) = utf8_seq_len(b) else {
+                        self.p = self.table.len();
+                        return None;
+                    };
+                    self.p += n;
+                    while self.in_seq {
+                        match self.table.get(self.p) {
+                            Some(&b) if !matches!(b, START_SEQ | END_REC) => {
+                                self.p += utf8_seq_len(b).unwrap_or(1);
+                            }
+                            _ => break,
+                        }
+                    }
+                    return Some((self.glyph, self.table.get(start..self.p)?));
+                }
+            }
+        }
+        None
+    }
+}
 
-        let off = (idx * self.bytes_per_glyph) as usize;
-        let end = off + self.bytes_per_glyph as usize;
-        self.glyphs.get(off..end)
+/// Walks a PSF1 unicode table: per glyph, little-endian UCS-2 codepoints, then optional
+/// `0xFFFE`-prefixed sequences, ending in `0xFFFF`. Yields `(glyph_index, codepoint)` for
+/// the single codepoints only, stopping after `num_glyphs` records.
+#[derive(Clone)]
+struct Ucs2Codepoints<'a> {
+    units: core::slice::ChunksExact<'a, u8>,
+    glyph: u32,
+    num_glyphs: u32,
+    in_seq: bool,
+}
+
+impl<'a> Ucs2Codepoints<'a> {
+    fn new(table: &'a [u8], num_glyphs: u32) -> Self {
+        Ucs2Codepoints {
+            units: table.chunks_exact(2),
+            glyph: 0,
+            num_glyphs,
+            in_seq: false,
+        }
+    }
+}
+
+impl Iterator for Ucs2Codepoints<'_> {
+    type Item = (u32, u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.glyph < self.num_glyphs {
+            let unit = self.units.next()?;
+            match u16::from_le_bytes([unit[0], unit[1]]) {
+                0xFFFF => {
+                    self.glyph += 1;
+                    self.in_seq = false;
+                }
+                0xFFFE => self.in_seq = true,
+                // UCS-2 has no surrogate pairs; a lone surrogate isn't a character.
+                0xD800..=0xDFFF => {}
+                cp if !self.in_seq => return Some((self.glyph, cp as u32)),
+                _ => {}
+            }
+        }
+        None
     }
 }
 
 impl PSF2Header {
     /// Create a new PSF2 header with the given parameters.
-    pub fn new(
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
         magic: [u8; 4],
         version: u32,
         header_size: u32,
@@ -259,43 +1404,107 @@ impl PSF2Header {
         }
     }
 
+    /// Checks every invariant of a well-formed PSF2 header: the magic number, version 0,
+    /// a `header_size` of at least 32, non-zero `width`, `height` and `num_glyphs`, and
+    /// `bytes_per_glyph == ceil(width / 8) * height`.
+    ///
+    /// [`from_bytes`](Self::from_bytes) and [`PSF2Font::new`] only check what they need to
+    /// locate the data, so this is also worth calling on parsed headers from untrusted
+    /// sources, not just on ones built with [`PSF2Header::new`].
+    pub const fn validate(&self) -> Result<(), PsfError> {
+        if u32::from_le_bytes(self.magic) != u32::from_le_bytes(PSF2_MAGIC) {
+            return Err(PsfError::InvalidMagic);
+        }
+        if self.version != 0 {
+            return Err(PsfError::UnsupportedVersion);
+        }
+        if self.header_size < HEADER_SIZE as u32 {
+            return Err(PsfError::InvalidHeaderSize);
+        }
+        if self.width == 0 || self.height == 0 || self.num_glyphs == 0 {
+            return Err(PsfError::InvalidDimensions);
+        }
+        // Widened so huge dimensions can't wrap around to a matching value.
+        let expected = self.width.div_ceil(8) as u64 * self.height as u64;
+        if self.bytes_per_glyph as u64 != expected {
+            return Err(PsfError::GlyphSizeMismatch);
+        }
+        Ok(())
+    }
+
+    /// Serializes the header back into its 32 on-disk bytes, every field little-endian,
+    /// e.g. to write back a header after editing it. The inverse of
+    /// [`from_bytes`](Self::from_bytes); no field is checked, see
+    /// [`validate`](Self::validate). A `header_size` above 32 is written as is, so the
+    /// extended header bytes after these are up to the caller.
+    pub const fn to_bytes(&self) -> [u8; 32] {
+        let fields = [
+            self.version,
+            self.header_size,
+            self.flags,
+            self.num_glyphs,
+            self.bytes_per_glyph,
+            self.height,
+            self.width,
+        ];
+        let mut bytes = [0u8; 32];
+        let (mut i, mut at) = (0, 0);
+        while i < 4 {
+            bytes[i] = self.magic[i];
+            i += 1;
+        }
+        while at < fields.len() {
+            let le = fields[at].to_le_bytes();
+            let mut i = 0;
+            while i < 4 {
+                bytes[4 + at * 4 + i] = le[i];
+                i += 1;
+            }
+            at += 1;
+        }
+        bytes
+    }
+
     /// Tries to parse a PSF2 header from a byte slice; returns an error if the header is invalid.
-    /// Each field is parsed as a little-endian u32, propagates errors if the parsing fails.
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+    /// Each field is parsed as a little-endian u32.
+    ///
+    /// That holds on big-endian hosts too: the crate never reads multi-byte values in host
+    /// order (every field goes through `from_le_bytes`, and bitmaps are read byte by byte),
+    /// so a font parses and renders identically on any target.
+    ///
+    /// This is a `const fn`, so the bytes are indexed manually rather than through `try_into`.
+    pub const fn from_bytes(bytes: &[u8]) -> Result<Self, PsfError> {
         if bytes.len() < 32 {
-            return Err("PSF2 header is too short");
+            return Err(PsfError::HeaderTooShort);
         }
 
-        fn le_u32(chunk: &[u8]) -> Result<u32, &'static str> {
-            let arr: [u8; 4] = chunk
-                .try_into()
-                .map_err(|_| "failed to read little-endian u32")?;
-            Ok(u32::from_le_bytes(arr))
+        // Callers guarantee `at + 4 <= bytes.len()`; we checked for 32 bytes above.
+        const fn le_u32(bytes: &[u8], at: usize) -> u32 {
+            u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
         }
 
-        let magic: [u8; 4] = bytes[0..4]
-            .try_into()
-            .map_err(|_| "failed to read magic number")?;
+        let magic: [u8; 4] = [bytes[0], bytes[1], bytes[2], bytes[3]];
 
         // Magic number must always be PSF2_MAGIC.
-        if magic != PSF2_MAGIC {
-            return Err("PSF2 magic number is invalid");
+        // (Array `==` isn't usable in `const fn`, so compare as a u32.)
+        if u32::from_le_bytes(magic) != u32::from_le_bytes(PSF2_MAGIC) {
+            return Err(PsfError::InvalidMagic);
         }
 
-        let version = le_u32(&bytes[4..8])?;
+        let version = le_u32(bytes, 4);
 
         // Version number must always be 0.
         if version != 0 {
-            return Err("PSF2 version is not supported");
+            return Err(PsfError::UnsupportedVersion);
         }
 
         // I would check if this is 32, but maybe it'll change.
-        let header_size = le_u32(&bytes[8..12])?;
-        let flags = le_u32(&bytes[12..16])?;
-        let num_glyphs = le_u32(&bytes[16..20])?;
-        let bytes_per_glyph = le_u32(&bytes[20..24])?;
-        let height = le_u32(&bytes[24..28])?;
-        let width = le_u32(&bytes[28..32])?;
+        let header_size = le_u32(bytes, 8);
+        let flags = le_u32(bytes, 12);
+        let num_glyphs = le_u32(bytes, 16);
+        let bytes_per_glyph = le_u32(bytes, 20);
+        let height = le_u32(bytes, 24);
+        let width = le_u32(bytes, 28);
 
         Ok(PSF2Header::new(
             magic,
@@ -0,0 +1,83 @@
+//! Rendering glyphs into byte-per-pixel buffers, for displays that take more than on/off.
+
+use crate::glyph::Glyph;
+
+/// Gray level for 0–4 set pixels out of a 2×2 block.
+const GRAY_LEVELS: [u8; 5] = [0, 64, 128, 192, 255];
+
+/// For each of `target` output positions, the source position it samples out of
+/// `source`, with Bresenham's error term spreading the skipped (or repeated) positions
+/// evenly instead of bunching them up.
+fn spread(source: usize, target: usize) -> impl Iterator<Item = usize> {
+    let (mut src, mut err) = (0, 0);
+    (0..target).map(move |_| {
+        let at = src;
+        err += source;
+        while err >= target {
+            err -= target;
+            src += 1;
+        }
+        at
+    })
+}
+
+impl Glyph<'_> {
+    /// Halves the glyph in both directions into a grayscale buffer, one byte per pixel:
+    /// each 2×2 block of source pixels becomes 0, 64, 128, 192 or 255 for 0–4 of them
+    /// set. E.g. to draw the 16×32 font as smoothed 8×16 text on a grayscale OLED.
+    ///
+    /// The output is `width / 2 × height / 2` pixels with its top-left corner at
+    /// `out[y0 * stride + x0]`, row `y` starting `stride` bytes after row `y - 1`.
+    /// An odd last column or row is dropped, and output pixels that fall outside `out`
+    /// (or past `stride`) are skipped.
+    pub fn downscale_2x_gray(&self, out: &mut [u8], stride: usize, x0: usize, y0: usize) {
+        let (w, h) = (self.width() / 2, self.height() / 2);
+        for y in 0..h {
+            let (Some(top), Some(bottom)) = (self.row(2 * y), self.row(2 * y + 1)) else {
+                return;
+            };
+            for x in (0..w).take_while(|x| x0 + x < stride) {
+                let set = [
+                    top.bit(2 * x),
+                    top.bit(2 * x + 1),
+                    bottom.bit(2 * x),
+                    bottom.bit(2 * x + 1),
+                ];
+                let level = GRAY_LEVELS[set.iter().filter(|&&on| on).count()];
+                if let Some(px) = out.get_mut((y0 + y) * stride + x0 + x) {
+                    *px = level;
+                }
+            }
+        }
+    }
+
+    /// Resamples the glyph to `target_w × target_h` pixels into a byte-per-pixel buffer,
+    /// e.g. to fit the 12×24 font into a 10×20 cell. Dropped (or, when enlarging,
+    /// doubled) columns and rows are spread evenly across the glyph, so strokes don't
+    /// vanish or thicken in one place as plain nearest-neighbor can make them.
+    ///
+    /// This is a binary resample: set pixels are written as 255 and clear ones as 0,
+    /// with no gray in between. The output is laid out and clipped like
+    /// [`downscale_2x_gray`](Self::downscale_2x_gray)'s.
+    pub fn resample(
+        &self,
+        target_w: usize,
+        target_h: usize,
+        out: &mut [u8],
+        stride: usize,
+        x0: usize,
+        y0: usize,
+    ) {
+        for (y, sy) in spread(self.height(), target_h).enumerate() {
+            let Some(row) = self.row(sy) else {
+                return;
+            };
+            let columns = spread(self.width(), target_w).enumerate();
+            for (x, sx) in columns.take_while(|(x, _)| x0 + x < stride) {
+                if let Some(px) = out.get_mut((y0 + y) * stride + x0 + x) {
+                    *px = if row.bit(sx) { 255 } else { 0 };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Per-cell text attributes for terminal grids.
+
+use core::ops::{BitOr, BitOrAssign};
+
+use crate::glyph::{Glyph, GlyphRow};
+
+/// A set of cell attributes, combined with `|`, e.g. `Attrs::BOLD | Attrs::UNDERLINE`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Attrs(u8);
+
+impl Attrs {
+    /// No attributes: the glyph as the font draws it.
+    pub const NONE: Attrs = Attrs(0);
+    /// Drawn with [`Glyph::bold`].
+    pub const BOLD: Attrs = Attrs(1 << 0);
+    /// Bottom row set, as in [`Glyph::with_underline`].
+    pub const UNDERLINE: Attrs = Attrs(1 << 1);
+    /// Reverse video, as in [`Glyph::inverted`]; applied last, so an underline flips too.
+    pub const INVERSE: Attrs = Attrs(1 << 2);
+    /// Carried for the caller, who knows the blink phase: it doesn't change the bitmap.
+    pub const BLINK: Attrs = Attrs(1 << 3);
+
+    /// The raw bits, e.g. to pack attributes into a terminal's cell array.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Attributes from raw bits; bits that aren't an attribute are dropped.
+    pub const fn from_bits_truncate(bits: u8) -> Attrs {
+        Attrs(bits & 0x0F)
+    }
+
+    /// Whether every attribute in `other` is set.
+    pub const fn contains(self, other: Attrs) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether no attribute is set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Attrs {
+    type Output = Attrs;
+
+    fn bitor(self, rhs: Attrs) -> Attrs {
+        Attrs(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Attrs {
+    fn bitor_assign(&mut self, rhs: Attrs) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// A glyph together with the attributes of the terminal cell it's drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StyledGlyph<'a> {
+    /// The glyph to draw.
+    pub glyph: Glyph<'a>,
+    /// How to draw it.
+    pub attrs: Attrs,
+}
+
+impl<'a> StyledGlyph<'a> {
+    pub fn new(glyph: Glyph<'a>, attrs: Attrs) -> Self {
+        StyledGlyph { glyph, attrs }
+    }
+
+    /// Yields the glyph's rows with [`BOLD`](Attrs::BOLD), [`UNDERLINE`](Attrs::UNDERLINE)
+    /// and [`INVERSE`](Attrs::INVERSE) applied in that order, each row computed as it is
+    /// yielded.
+    pub fn rows(&self) -> impl Iterator<Item = GlyphRow<'a>> {
+        let attrs = self.attrs;
+        let width = self.glyph.width();
+        let last = self.glyph.len().checked_sub(1);
+        self.glyph.enumerate().map(move |(y, row)| {
+            let row = if attrs.contains(Attrs::UNDERLINE) && Some(y) == last {
+                GlyphRow::from_fn(width, |_| true)
+            } else if attrs.contains(Attrs::BOLD) {
+                row.bold()
+            } else {
+                row
+            };
+            if attrs.contains(Attrs::INVERSE) {
+                row.inverted()
+            } else {
+                row
+            }
+        })
+    }
+
+    /// Calls `f(x, y, on)` for every pixel of the styled cell, in row-major order; see
+    /// [`Glyph::blit`].
+    pub fn blit(&self, mut f: impl FnMut(usize, usize, bool)) {
+        for (y, row) in self.rows().enumerate() {
+            for (x, on) in row.enumerate() {
+                f(x, y, on);
+            }
+        }
+    }
+}
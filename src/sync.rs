@@ -0,0 +1,159 @@
+//! A font that can be shared between threads or cores behind a plain `&`.
+//!
+//! [`PSF2Font`] only needs `&mut self` for lookups because of its glyph cache; the glyph
+//! bitmaps and unicode table are read-only. [`SyncPSF2Font`] keeps those in an uncached
+//! font and moves the cache behind a spinlock, so one parsed font can serve every core.
+//!
+//! The lock is only held to probe or update the cache, never while scanning the unicode
+//! table, and ASCII/direct lookups don't take it at all. Under heavy contention cores spin
+//! on each other's cache accesses instead of running them in parallel, and two cores
+//! missing on the same character will both scan the table. If each core renders mostly
+//! different text, a [`PSF2Font`] per core avoids the lock entirely at the cost of its
+//! cache RAM per copy.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cache::{Cache, CacheStats, DEFAULT_CACHE_SIZE};
+use crate::error::PsfError;
+use crate::glyph::Glyph;
+use crate::psf::{OobPolicy, PSF2Font};
+use crate::utf8::is_complete_utf8;
+
+/// A minimal test-and-set spinlock; critical sections here are a few dozen instructions.
+struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: `value` is only reached through `with`, which holds the lock for the duration.
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    const fn new(value: T) -> Self {
+        SpinLock {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        // SAFETY: holding the lock gives exclusive access until it is released below.
+        let r = f(unsafe { &mut *self.value.get() });
+        self.locked.store(false, Ordering::Release);
+        r
+    }
+}
+
+/// A [`PSF2Font`] whose lookups take `&self`, with a lock-guarded glyph cache of
+/// `CACHE` entries. See the [module docs](self) for the contention tradeoff.
+pub struct SyncPSF2Font<'a, const CACHE: usize = DEFAULT_CACHE_SIZE> {
+    font: PSF2Font<'a, 0>,
+    cache: SpinLock<Cache<CACHE>>,
+}
+
+impl<'a> SyncPSF2Font<'a> {
+    /// Parses a font from a byte slice; see [`PSF2Font::new`].
+    pub const fn new(data: &'a [u8]) -> Result<Self, PsfError> {
+        Self::with_cache_size(data)
+    }
+}
+
+impl<'a, const CACHE: usize> SyncPSF2Font<'a, CACHE> {
+    /// Like [`SyncPSF2Font::new`], with a glyph cache of `CACHE` entries.
+    pub const fn with_cache_size(data: &'a [u8]) -> Result<Self, PsfError> {
+        match PSF2Font::with_cache_size(data) {
+            Ok(font) => Ok(SyncPSF2Font {
+                font,
+                cache: SpinLock::new(Cache::new()),
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sets what out-of-range glyph indices resolve to; see [`PSF2Font::set_oob_policy`].
+    pub fn set_oob_policy(&mut self, policy: OobPolicy) {
+        self.font.set_oob_policy(policy);
+    }
+
+    /// Switches to the font in `data` in place and clears the cache; see [`PSF2Font::reload`].
+    pub fn reload(&mut self, data: &'a [u8]) -> Result<(), PsfError> {
+        self.font.reload(data)?;
+        self.cache = SpinLock::new(Cache::new());
+        Ok(())
+    }
+
+    /// Indexes single codepoints for binary search; see [`PSF2Font::build_index`].
+    pub fn build_index(&mut self, scratch: &'a mut [(u32, u32)]) -> usize {
+        self.font.build_index(scratch)
+    }
+
+    /// The underlying font, for its metrics and the lookups that don't need a cache.
+    pub fn font(&self) -> &PSF2Font<'a, 0> {
+        &self.font
+    }
+
+    /// Returns a `Glyph` for a given UTF-8 byte slice; see [`PSF2Font::glyph_for_utf8`].
+    pub fn glyph_for_utf8(&self, text: &[u8]) -> Option<Glyph<'a>> {
+        let slice = self.get_glyph_data(text)?;
+        Some(self.font.glyph_from(slice))
+    }
+
+    /// Returns the `Glyph` for a single character.
+    pub fn glyph_for_char(&self, c: char) -> Option<Glyph<'a>> {
+        let mut buf = [0u8; 4];
+        self.glyph_for_utf8(c.encode_utf8(&mut buf).as_bytes())
+    }
+
+    /// Returns glyph data for a given UTF-8 byte slice, taking the same paths as
+    /// [`PSF2Font::get_glyph_data`].
+    pub fn get_glyph_data(&self, text: &[u8]) -> Option<&'a [u8]> {
+        let font = &self.font;
+        if font.is_direct(text) {
+            return font.glyph_by_idx(font.direct_index(text)?);
+        }
+
+        if !is_complete_utf8(text) {
+            return None;
+        }
+
+        if let Some(offset) = self.cache.with(|cache| cache.get(text)) {
+            return font.glyph_at_offset(offset);
+        }
+
+        // Scan outside the lock so other cores' hits aren't stalled behind it.
+        let idx = font.scan_unicode_table(text)?;
+        if let Some(offset) = font.glyph_offset(idx) {
+            self.cache.with(|cache| cache.insert(text, offset));
+        }
+        font.glyph_by_idx(idx)
+    }
+
+    /// Resolves every character of `text` into the glyph cache; see [`PSF2Font::prime_cache`].
+    /// Table scans run outside the lock, as in [`get_glyph_data`](Self::get_glyph_data).
+    pub fn prime_cache(&self, text: &str) {
+        let mut buf = [0u8; 4];
+        for c in text.chars() {
+            let key = c.encode_utf8(&mut buf).as_bytes();
+            if self.font.is_direct(key) || self.cache.with(|cache| cache.peek(key)).is_some() {
+                continue;
+            }
+            let idx = self.font.scan_unicode_table(key);
+            if let Some(offset) = idx.and_then(|i| self.font.glyph_offset(i)) {
+                self.cache.with(|cache| cache.insert(key, offset));
+            }
+        }
+    }
+
+    /// Hit/miss counters of the shared glyph cache, summed over all threads.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.with(|cache| cache.stats())
+    }
+}
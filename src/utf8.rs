@@ -0,0 +1,94 @@
+//! Classifying and decoding UTF-8 without going through `core::str`.
+
+/// The length of the UTF-8 sequence that starts with `first`, or `None` if `first`
+/// can't start one.
+///
+/// The leading byte announces how many bytes the sequence has; the rest are
+/// continuation bytes of the form `10xxxxxx`:
+///
+/// | `first`       | bit pattern | length |
+/// |---------------|-------------|--------|
+/// | `0x00..=0x7F` | `0xxxxxxx`  | 1      |
+/// | `0xC2..=0xDF` | `110xxxxx`  | 2      |
+/// | `0xE0..=0xEF` | `1110xxxx`  | 3      |
+/// | `0xF0..=0xF4` | `11110xxx`  | 4      |
+///
+/// Continuation bytes (`0x80..=0xBF`) are rejected, as are `0xC0`/`0xC1`, which could
+/// only start overlong encodings of ASCII, and `0xF5..=0xFF`, which would encode
+/// codepoints past U+10FFFF. Only the leading byte is looked at: the continuation bytes
+/// still need checking, and `0xE0`, `0xED`, `0xF0` and `0xF4` also restrict the range
+/// of the byte after them.
+///
+/// ```
+/// use spleen_font::utf8::utf8_seq_len;
+///
+/// assert_eq!(utf8_seq_len(b'A'), Some(1));
+/// assert_eq!(utf8_seq_len("é".as_bytes()[0]), Some(2));
+/// assert_eq!(utf8_seq_len(0x80), None);
+/// ```
+#[inline]
+pub const fn utf8_seq_len(first: u8) -> Option<usize> {
+    Some(match first {
+        0x00..=0x7F => 1, // 0xxxxxxx
+        0xC2..=0xDF => 2, // 110xxxxx
+        0xE0..=0xEF => 3, // 1110xxxx
+        0xF0..=0xF4 => 4, // 11110xxx
+        _ => return None, // continuation or invalid
+    })
+}
+
+/// Checks that `bytes` is one or more complete, well-formed UTF-8 scalars: each leading
+/// byte is followed by exactly as many continuation bytes as it announces, and there are
+/// no overlong encodings, surrogates or codepoints past U+10FFFF.
+pub(crate) fn is_complete_utf8(bytes: &[u8]) -> bool {
+    let mut p = 0;
+    while p < bytes.len() {
+        let Some(n) = utf8_seq_len(bytes[p]) else {
+            return false;
+        };
+        // A leading byte too close to the end means the scalar was cut off.
+        let Some(scalar) = bytes.get(p..p + n) else {
+            return false;
+        };
+        // The leading bytes that can start an invalid scalar are caught by narrowing
+        // the range of the first continuation byte.
+        let second_ok = match (scalar[0], scalar.get(1)) {
+            (_, None) => true,
+            (0xE0, Some(&b)) => (0xA0..=0xBF).contains(&b),
+            (0xED, Some(&b)) => (0x80..=0x9F).contains(&b),
+            (0xF0, Some(&b)) => (0x90..=0xBF).contains(&b),
+            (0xF4, Some(&b)) => (0x80..=0x8F).contains(&b),
+            (_, Some(&b)) => b & 0xC0 == 0x80,
+        };
+        if !second_ok || scalar.iter().skip(2).any(|&b| b & 0xC0 != 0x80) {
+            return false;
+        }
+        p += n;
+    }
+    !bytes.is_empty()
+}
+
+/// Decodes a byte slice holding exactly one UTF-8 scalar into its codepoint.
+/// Returns None if the slice is empty, malformed, or holds more than one scalar.
+pub(crate) fn decode_utf8(bytes: &[u8]) -> Option<u32> {
+    let (&first, rest) = bytes.split_first()?;
+    let n = utf8_seq_len(first)?;
+    if bytes.len() != n || !is_complete_utf8(bytes) {
+        return None;
+    }
+
+    // Strip the length marker off the leading byte, then fold in 6 bits per continuation byte.
+    let lead = match n {
+        1 => first as u32,
+        2 => (first & 0x1F) as u32,
+        3 => (first & 0x0F) as u32,
+        _ => (first & 0x07) as u32,
+    };
+    rest.iter().try_fold(lead, |cp, &b| {
+        if b & 0xC0 == 0x80 {
+            Some((cp << 6) | (b & 0x3F) as u32)
+        } else {
+            None
+        }
+    })
+}
@@ -0,0 +1,154 @@
+//! Serializes glyph bitmaps back out to a PSF2 blob.
+//!
+//! The writer never allocates: the caller provides the output buffer, sized with
+//! [`PSF2Writer::encoded_len`].
+use crate::error::PsfError;
+use crate::psf::PSF2_MAGIC;
+
+/// Size of the header we emit. We never write extended headers.
+pub(crate) const HEADER_SIZE: usize = 32;
+
+/// Terminates each glyph's record in the unicode table.
+pub(crate) const END_REC: u8 = 0xFF;
+
+/// Bytes of one `width × height` glyph, or `None` if that doesn't fit the header's
+/// 32-bit `bytes_per_glyph` field.
+pub(crate) fn glyph_size(width: u32, height: u32) -> Option<u32> {
+    width.div_ceil(8).checked_mul(height)
+}
+
+/// Fills a 32-byte PSF2 header for `num_glyphs` glyphs of `width × height` pixels,
+/// `bytes_per_glyph` bytes each.
+pub(crate) fn write_header(
+    header: &mut [u8],
+    flags: u32,
+    num_glyphs: u32,
+    bytes_per_glyph: u32,
+    width: u32,
+    height: u32,
+) {
+    header[0..4].copy_from_slice(&PSF2_MAGIC);
+    let fields = [
+        0, // version
+        HEADER_SIZE as u32,
+        flags,
+        num_glyphs,
+        bytes_per_glyph,
+        height,
+        width,
+    ];
+    for (chunk, field) in header[4..HEADER_SIZE].chunks_exact_mut(4).zip(fields) {
+        chunk.copy_from_slice(&field.to_le_bytes());
+    }
+}
+
+/// Emits a PSF2 font from header fields, glyph bitmaps and a codepoint→index map.
+///
+/// ```rust
+/// use spleen_font::{PSF2Font, PSF2Writer};
+///
+/// // Two 8×2 glyphs: a solid bar and a hollow box.
+/// let bitmaps = [0xFF, 0xFF, 0x81, 0x81];
+/// let map = [('█', 0), ('□', 1)];
+///
+/// let writer = PSF2Writer::new(8, 2, &bitmaps).with_unicode(&map);
+/// let mut buf = [0u8; 64];
+/// let len = writer.write(&mut buf).unwrap();
+///
+/// let font = PSF2Font::new(&buf[..len]).unwrap();
+/// assert_eq!(font.num_glyphs, 2);
+/// assert_eq!(font.glyph_by_idx(1), Some(&[0x81, 0x81][..]));
+/// ```
+#[derive(Clone, Copy)]
+pub struct PSF2Writer<'w> {
+    /// Width of each glyph in pixels.
+    width: u32,
+    /// Height of each glyph in pixels.
+    height: u32,
+    /// Packed glyph bitmaps, `bytes_per_glyph` bytes each, back to back.
+    glyphs: &'w [u8],
+    /// Characters and the glyph index each one maps to.
+    unicode: &'w [(char, u32)],
+}
+
+impl<'w> PSF2Writer<'w> {
+    /// Creates a writer for `width × height` glyphs stored back to back in `glyphs`.
+    /// Rows are packed MSB-first and padded to whole bytes, like the font data we read.
+    pub fn new(width: u32, height: u32, glyphs: &'w [u8]) -> Self {
+        PSF2Writer {
+            width,
+            height,
+            glyphs,
+            unicode: &[],
+        }
+    }
+
+    /// Adds a unicode table. Several characters may map to the same glyph index.
+    /// Without one, readers map codepoints straight to glyph indices.
+    pub fn with_unicode(self, unicode: &'w [(char, u32)]) -> Self {
+        PSF2Writer { unicode, ..self }
+    }
+
+    /// Size of each glyph in bytes, or `u32::MAX` if the dimensions need more than a
+    /// PSF2 header can hold; [`write`](Self::write) rejects those.
+    pub fn bytes_per_glyph(&self) -> u32 {
+        glyph_size(self.width, self.height).unwrap_or(u32::MAX)
+    }
+
+    /// Number of glyphs that will be written.
+    pub fn num_glyphs(&self) -> u32 {
+        match self.bytes_per_glyph() {
+            0 => 0,
+            n => (self.glyphs.len() / n as usize) as u32,
+        }
+    }
+
+    /// Exact number of bytes [`write`](Self::write) needs.
+    pub fn encoded_len(&self) -> usize {
+        let table = if self.unicode.is_empty() {
+            0
+        } else {
+            let chars: usize = self.unicode.iter().map(|(c, _)| c.len_utf8()).sum();
+            chars + self.num_glyphs() as usize
+        };
+        HEADER_SIZE + self.glyphs.len() + table
+    }
+
+    /// Writes the font into `out` and returns the number of bytes written.
+    ///
+    /// The unicode table has one record per glyph, each terminated by `0xFF`, so
+    /// unmapped glyphs (and any glyphs left at the end) produce runs of `0xFF 0xFF`.
+    pub fn write(&self, out: &mut [u8]) -> Result<usize, PsfError> {
+        let size = glyph_size(self.width, self.height).ok_or(PsfError::InvalidDimensions)?;
+        let bytes_per_glyph = size as usize;
+        if bytes_per_glyph == 0 || !self.glyphs.len().is_multiple_of(bytes_per_glyph) {
+            return Err(PsfError::GlyphSizeMismatch);
+        }
+        let num_glyphs = self.num_glyphs();
+        if self.unicode.iter().any(|&(_, idx)| idx >= num_glyphs) {
+            return Err(PsfError::GlyphIndexOutOfRange);
+        }
+        let len = self.encoded_len();
+        let out = out.get_mut(..len).ok_or(PsfError::BufferTooSmall)?;
+
+        let flags = if self.unicode.is_empty() { 0 } else { 1 };
+        let (header, rest) = out.split_at_mut(HEADER_SIZE);
+        write_header(header, flags, num_glyphs, size, self.width, self.height);
+
+        let (bitmaps, table) = rest.split_at_mut(self.glyphs.len());
+        bitmaps.copy_from_slice(self.glyphs);
+
+        if flags != 0 {
+            let mut p = 0;
+            for idx in 0..num_glyphs {
+                for &(c, _) in self.unicode.iter().filter(|&&(_, i)| i == idx) {
+                    p += c.encode_utf8(&mut table[p..]).len();
+                }
+                table[p] = END_REC;
+                p += 1;
+            }
+        }
+
+        Ok(len)
+    }
+}